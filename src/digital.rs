@@ -1,16 +1,9 @@
-use std::{collections::HashMap, sync::Arc};
-
 use rand::Rng;
 use xmlwriter::XmlWriter;
 
-use crate::types::module::{ExternalModule, Module};
-
-mod expression;
-mod module;
-mod program;
-mod variable_definition;
+mod cell;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Coordinate {
     pub x: i64,
     pub y: i64,
@@ -29,7 +22,10 @@ impl Coordinate {
 
         unsafe {
             // todo: make this look a bit nicer
-            let ret = CURRENT_COORDINATE.clone();
+            let ret = Coordinate {
+                x: CURRENT_COORDINATE.x,
+                y: CURRENT_COORDINATE.y,
+            };
 
             // we are incrementing both on purpose, to make sure wires
             // somehow don't overlap
@@ -195,81 +191,9 @@ impl VisualElement {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct CircuitVariable {
-    name: String,
-    data: DigitalData,
-    undefined: bool,
-}
-
-#[derive(Debug, Clone)]
-pub enum CircuitModule {
-    Internal(Module),
-    External(ExternalModule),
-}
-
-impl CircuitModule {
-    pub fn get_name(&self) -> String {
-        match self {
-            CircuitModule::Internal(module) => module.name.clone(),
-            CircuitModule::External(module) => module.name.clone(),
-        }
-    }
-}
-
-#[derive(Debug)]
-pub struct CurrentModule {
-    variables: Vec<CircuitVariable>,
-}
-
 pub struct Circuit {
     wires: Vec<Wire>,
     visual_elements: Vec<VisualElement>,
-
-    modules: Vec<CircuitModule>,
-
-    current_module: Vec<CurrentModule>,
-}
-
-#[derive(Debug, Clone)]
-pub enum DigitalData {
-    Empty,
-    Wire(u32, Coordinate),
-    Object(HashMap<String, Arc<DigitalData>>),
-}
-
-impl DigitalData {
-    pub fn get_size(&self) -> u32 {
-        match self {
-            DigitalData::Empty => panic!("Empty data has no size"),
-            DigitalData::Wire(size, _) => *size,
-            DigitalData::Object(map) => {
-                if map.keys().len() != 1 {
-                    panic!("! Object width has more than one key");
-                }
-
-                map.values().next().unwrap().as_ref().get_size()
-            }
-        }
-    }
-    pub fn get_position(&self) -> Coordinate {
-        match self {
-            DigitalData::Empty => panic!("Empty data has no position"),
-            DigitalData::Wire(.., position) => position.clone(),
-            DigitalData::Object(map) => {
-                if map.keys().len() != 1 {
-                    panic!("Object width has more than one key");
-                }
-
-                map.values().next().unwrap().as_ref().get_position()
-            }
-        }
-    }
-}
-
-pub trait ToDigital {
-    // returns wire positions
-    fn convert_to_digital(&self, circuit: &mut Circuit) -> DigitalData;
 }
 
 impl Circuit {
@@ -277,39 +201,7 @@ impl Circuit {
         Self {
             wires: vec![],
             visual_elements: vec![],
-
-            modules: vec![],
-
-            current_module: vec![CurrentModule { variables: vec![] }],
-        }
-    }
-
-    pub fn is_top(&self) -> bool {
-        self.current_module.len() <= 1
-    }
-
-    pub fn add_variable(&mut self, variable: CircuitVariable) {
-        self.current_module
-            .last_mut()
-            .unwrap()
-            .variables
-            .push(variable);
-    }
-
-    pub fn find_variable(&self, name: String) -> Option<&CircuitVariable> {
-        for module in self.current_module.iter().rev() {
-            for variable in &module.variables {
-                if variable.name == name {
-                    return Some(variable);
-                }
-            }
         }
-
-        None
-    }
-
-    pub fn find_module(&self, name: &str) -> Option<&CircuitModule> {
-        self.modules.iter().find(|module| module.get_name() == name)
     }
 
     pub fn as_xml(&self) -> String {
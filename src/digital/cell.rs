@@ -0,0 +1,352 @@
+use std::collections::HashMap;
+
+use crate::netlist::{Cell, CellKind, Direction, GateOp, NetId, Netlist};
+
+use super::{Circuit, Coordinate, Entry, EntryValue, VisualElement, Wire};
+
+fn entry(name: &str, value: EntryValue) -> Entry {
+    Entry {
+        name: String::from(name),
+        value,
+    }
+}
+
+// the name of the gate in Digital, and how far right its output is
+fn gate_element(op: GateOp) -> (&'static str, i64) {
+    match op {
+        GateOp::And => ("And", 80),
+        GateOp::NAnd => ("NAnd", 100),
+        GateOp::Or => ("Or", 80),
+        GateOp::NOr => ("NOr", 100),
+        GateOp::XOr => ("XOr", 80),
+        GateOp::XNOr => ("XNOr", 100),
+    }
+}
+
+// `1 * 4` for four single bits, `4, 2` otherwise
+fn splitting(widths: &[u32]) -> String {
+    if widths.len() > 1 && widths.iter().all(|width| *width == 1) {
+        return format!("1 * {}", widths.len());
+    }
+
+    widths
+        .iter()
+        .map(|width| width.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// the cell of a slice, and the first and last bit it takes
+type SliceBits = (usize, u32, u32);
+
+impl Circuit {
+    pub fn from_netlist(netlist: &Netlist) -> Self {
+        let mut circuit = Circuit::new();
+
+        let mut slices = circuit.place_slices(netlist);
+
+        let positions: Vec<_> = netlist
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| {
+                slices
+                    .remove(&index)
+                    .unwrap_or_else(|| circuit.place(cell, netlist))
+            })
+            .collect();
+
+        // every net starts at the first pin that drives it, or at a point of
+        // its own if nothing drives it
+        let mut anchors = vec![None; netlist.nets.len()];
+
+        for (cell, positions) in netlist.cells.iter().zip(&positions) {
+            for (pin, position) in cell.pins.iter().zip(positions) {
+                if pin.direction != Direction::In && anchors[pin.net].is_none() {
+                    anchors[pin.net] = Some(position.clone());
+                }
+            }
+        }
+
+        let anchors: Vec<_> = anchors
+            .into_iter()
+            .map(|anchor| anchor.unwrap_or_else(Coordinate::next))
+            .collect();
+
+        for (cell, positions) in netlist.cells.iter().zip(&positions) {
+            for (pin, position) in cell.pins.iter().zip(positions) {
+                if *position != anchors[pin.net] {
+                    circuit.wires.push(Wire {
+                        start: anchors[pin.net].clone(),
+                        end: position.clone(),
+                    });
+                }
+            }
+        }
+
+        circuit
+    }
+
+    fn add_element(&mut self, name: &str, attributes: Vec<Entry>) -> Coordinate {
+        let position = Coordinate::next();
+
+        self.visual_elements.push(VisualElement {
+            name: String::from(name),
+            attributes,
+            position: position.clone(),
+        });
+
+        position
+    }
+
+    fn add_splitter(&mut self, input: String, output: String) -> Coordinate {
+        self.add_element(
+            "Splitter",
+            vec![
+                entry("Input Splitting", EntryValue::String(input)),
+                entry("Output Splitting", EntryValue::String(output)),
+            ],
+        )
+    }
+
+    // the slices of the same net share a splitter with an output for each of
+    // them, as long as they don't overlap. returns the pins of every slice by
+    // the index of its cell.
+    fn place_slices(&mut self, netlist: &Netlist) -> HashMap<usize, Vec<Coordinate>> {
+        let mut groups: Vec<(NetId, Vec<SliceBits>)> = vec![];
+
+        let slices = netlist
+            .cells
+            .iter()
+            .enumerate()
+            .filter_map(|(index, cell)| match cell.kind {
+                CellKind::Slice(from, to) => Some((index, cell.pin("in")?, from, to)),
+                _ => None,
+            });
+
+        for (index, input, from, to) in slices {
+            let overlaps = |group: &Vec<SliceBits>| {
+                group
+                    .iter()
+                    .any(|(_, other_from, other_to)| from <= *other_to && *other_from <= to)
+            };
+
+            match groups
+                .iter_mut()
+                .find(|(net, group)| *net == input && !overlaps(group))
+            {
+                Some((_, group)) => group.push((index, from, to)),
+                None => groups.push((input, vec![(index, from, to)])),
+            }
+        }
+
+        let mut positions = HashMap::new();
+
+        for (input, mut group) in groups {
+            group.sort_by_key(|(_, from, _)| *from);
+
+            let outputs: Vec<_> = group
+                .iter()
+                .map(|(_, from, to)| format!("{} - {}", from, to))
+                .collect();
+
+            let position = self.add_splitter(netlist.width(input).to_string(), outputs.join(", "));
+
+            for (output, (index, _, _)) in group.iter().enumerate() {
+                positions.insert(
+                    *index,
+                    vec![position.clone(), position.add(20, 20 * output as i64)],
+                );
+            }
+        }
+
+        positions
+    }
+
+    // a gate for every bit, between a splitter for every input and one that
+    // joins the outputs. returns the inputs and the output.
+    fn add_bitwise(
+        &mut self,
+        name: &str,
+        attributes: Vec<Entry>,
+        inputs: usize,
+        output_x: i64,
+        width: u32,
+    ) -> (Vec<Coordinate>, Coordinate) {
+        // a single bit needs no splitters
+        if width == 1 {
+            let gate = self.add_element(name, attributes);
+
+            let pins = (0..inputs as i64)
+                .map(|input| gate.add(0, 40 * input))
+                .collect();
+
+            return (pins, gate.add(output_x, 20 * (inputs as i64 - 1)));
+        }
+
+        let output = self.add_splitter(format!("1 * {}", width), width.to_string());
+
+        let splitters: Vec<_> = (0..inputs)
+            .map(|_| self.add_splitter(width.to_string(), format!("1 * {}", width)))
+            .collect();
+
+        for i in 0..width as i64 {
+            let gate = self.add_element(name, attributes.clone());
+
+            for (input, splitter) in splitters.iter().enumerate() {
+                self.wires.push(Wire {
+                    start: splitter.add(20, 20 * i),
+                    end: gate.add(0, 40 * input as i64),
+                });
+            }
+
+            self.wires.push(Wire {
+                start: gate.add(output_x, 20 * (inputs as i64 - 1)),
+                end: output.add(0, 20 * i),
+            });
+        }
+
+        (splitters, output.add(20, 0))
+    }
+
+    // adds the elements of a cell, returns where each of its pins is
+    fn place(&mut self, cell: &Cell, netlist: &Netlist) -> Vec<Coordinate> {
+        let width = |name: &str| netlist.width(cell.pin(name).unwrap());
+
+        match &cell.kind {
+            CellKind::Const(value) => {
+                let position = self.add_element(
+                    "Const",
+                    vec![
+                        entry("Value", EntryValue::Long(*value as i64)),
+                        entry("Bits", EntryValue::Integer(width("out") as i32)),
+                    ],
+                );
+
+                vec![position]
+            }
+            CellKind::Gate(op) => {
+                let (name, output_x) = gate_element(*op);
+                let attributes = vec![entry("wideShape", EntryValue::Boolean(true))];
+
+                let (inputs, output) =
+                    self.add_bitwise(name, attributes, 2, output_x, width("out"));
+
+                vec![inputs[0].clone(), inputs[1].clone(), output]
+            }
+            CellKind::Not => {
+                let (inputs, output) = self.add_bitwise("Not", vec![], 1, 40, width("out"));
+
+                vec![inputs[0].clone(), output]
+            }
+            // see `place_slices`
+            CellKind::Slice(..) => unreachable!(),
+            CellKind::Concat => {
+                let parts: Vec<_> = cell.inputs().map(|pin| netlist.width(pin.net)).collect();
+
+                let position = self.add_splitter(splitting(&parts), width("out").to_string());
+
+                cell.pins
+                    .iter()
+                    .map(|pin| match pin.name.parse::<i64>() {
+                        Ok(index) => position.add(0, 20 * index),
+                        Err(_) => position.add(20, 0),
+                    })
+                    .collect()
+            }
+            CellKind::Multiplexer => {
+                let selector_bits = width("sel");
+                let count = 1i64 << selector_bits;
+
+                let position = self.add_element(
+                    "Multiplexer",
+                    vec![
+                        entry("Bits", EntryValue::Integer(width("out") as i32)),
+                        entry("Selector Bits", EntryValue::Integer(selector_bits as i32)),
+                    ],
+                );
+
+                cell.pins
+                    .iter()
+                    .map(|pin| match pin.name.as_str() {
+                        "sel" => position.add(20, 20 * count),
+                        "out" => position.add(40, 10 * count),
+                        index => position.add(0, 20 * index.parse::<i64>().unwrap()),
+                    })
+                    .collect()
+            }
+            CellKind::Driver => {
+                let position = self.add_element(
+                    "Driver",
+                    vec![entry("Bits", EntryValue::Integer(width("out") as i32))],
+                );
+
+                vec![
+                    position.add(-20, 0),
+                    position.add(0, -20),
+                    position.add(20, 0),
+                ]
+            }
+            CellKind::Input { label, default } => {
+                let mut attributes = vec![
+                    entry("Label", EntryValue::String(label.clone())),
+                    entry("Bits", EntryValue::Integer(width("out") as i32)),
+                ];
+
+                if let Some(default) = default {
+                    attributes.push(entry("Default", EntryValue::Integer(*default as i32)));
+                }
+
+                // an inout pin of the circuit
+                if cell.pins[0].direction == Direction::InOut {
+                    attributes.push(entry("isHighZ", EntryValue::Boolean(true)));
+                }
+
+                vec![self.add_element("In", attributes)]
+            }
+            CellKind::Clock { label, frequency } => {
+                let mut attributes = vec![entry("Label", EntryValue::String(label.clone()))];
+
+                if let Some(frequency) = frequency {
+                    attributes.push(entry("Frequency", EntryValue::Integer(*frequency as i32)));
+                }
+
+                attributes.push(entry(
+                    "runRealTime",
+                    EntryValue::Boolean(frequency.is_some()),
+                ));
+
+                vec![self.add_element("Clock", attributes)]
+            }
+            CellKind::Output { label } => {
+                let position = self.add_element(
+                    "Out",
+                    vec![
+                        entry("Label", EntryValue::String(label.clone())),
+                        entry("Bits", EntryValue::Integer(width("in") as i32)),
+                    ],
+                );
+
+                vec![position]
+            }
+            CellKind::External(module) => {
+                let position = self.add_element(&module.internal_name, module.attributes.clone());
+
+                cell.pins
+                    .iter()
+                    .map(|pin| {
+                        let port = module
+                            .inputs
+                            .iter()
+                            .chain(&module.outputs)
+                            .chain(&module.inouts)
+                            .find(|port| port.name == pin.name)
+                            .unwrap();
+
+                        position.add(port.position.x, port.position.y)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, fmt, fs, path::Path};
+
+use crate::{preprocessor::SourceLocation, types::source_info::SourceInfo};
+
+mod rules;
+
+pub use rules::check;
+
+// every lint, all of them are reported unless they are turned off. the
+// resolver checks the first four while it resolves the program, the others
+// are checked afterwards by `check`.
+pub const LINTS: &[&str] = &[
+    // a value is implicitly cut off or extended
+    "width",
+    // a wire, or a field of it, is never read
+    "unused",
+    // a `@wire` is declared, but nothing drives it
+    "undriven",
+    // a signal is computed from itself without going through storage
+    "loop",
+    "unused-input",
+    "constant-output",
+    // a wire in a module has the name of a global
+    "shadowed",
+    // both inputs of a gate are the same signal
+    "same-inputs",
+    "no-outputs",
+    // a multiplexer has values that its selector can never select
+    "mux-range",
+];
+
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub lint: &'static str,
+    pub location: Option<SourceLocation>,
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: {}", location, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// the lints that are turned on or off, the last setting wins
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    enabled: HashMap<&'static str, bool>,
+}
+
+impl LintOptions {
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let lint = LINTS
+            .iter()
+            .find(|lint| **lint == name)
+            .ok_or_else(|| format!("Unknown lint {}", name))?;
+
+        self.enabled.insert(lint, enabled);
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled.get(name).copied().unwrap_or(true)
+    }
+
+    // a config file has one `lint = on` or `lint = off` per line, `#` starts
+    // a comment
+    pub fn load(&mut self, path: &Path) -> Result<(), String> {
+        let config = fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+
+        for (number, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |message: String| format!("{}:{}: {}", path.display(), number + 1, message);
+
+            let (name, value) = line.split_once('=').ok_or_else(|| {
+                error(format!(
+                    "Expected `lint = on` or `lint = off`, got {}",
+                    line
+                ))
+            })?;
+
+            let enabled = match value.trim() {
+                "on" => true,
+                "off" => false,
+                value => return Err(error(format!("Expected on or off, got {}", value))),
+            };
+
+            self.set(name.trim(), enabled).map_err(error)?;
+        }
+
+        Ok(())
+    }
+
+    // the warning for `lint` at `source`, unless the lint is turned off or
+    // the statement allows it
+    pub fn report(
+        &self,
+        source: &SourceInfo,
+        lint: &'static str,
+        message: String,
+    ) -> Option<Warning> {
+        if !self.is_enabled(lint) || source.allows(lint) {
+            return None;
+        }
+
+        Some(Warning {
+            lint,
+            location: source.location.clone(),
+            message,
+        })
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+// one object per warning, the file and line are null if they aren't known
+pub fn to_json(warnings: &[Warning]) -> String {
+    let warnings: Vec<_> = warnings
+        .iter()
+        .map(|warning| {
+            let (file, line) = match &warning.location {
+                Some(location) => (json_string(&location.file), location.line.to_string()),
+                None => (String::from("null"), String::from("null")),
+            };
+
+            format!(
+                "  {{\"lint\": {}, \"file\": {}, \"line\": {}, \"message\": {}}}",
+                json_string(warning.lint),
+                file,
+                line,
+                json_string(&warning.message)
+            )
+        })
+        .collect();
+
+    if warnings.is_empty() {
+        String::from("[]")
+    } else {
+        format!("[\n{}\n]", warnings.join(",\n"))
+    }
+}
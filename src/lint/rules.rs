@@ -0,0 +1,320 @@
+use crate::{
+    parser::datatype::KnownBitWidth,
+    types::{
+        decorator::Decorator,
+        expression::{
+            BinaryOp, Combine, Drive, Expression, Extract, ExtractInner, Repeat, UnaryOp,
+        },
+        module::Module,
+        program::{Program, ProgramStatement},
+        source_info::SourceInfo,
+        variable_definition::VariableDefinitions,
+    },
+};
+
+use super::{LintOptions, Warning};
+
+// calls `f` with `expression` and everything inside of it
+fn visit(expression: &Expression, f: &mut impl FnMut(&Expression)) {
+    f(expression);
+
+    match expression {
+        Expression::Integer(_) | Expression::String(_) | Expression::Variable(_) => {}
+        Expression::Extract(Extract { expression, .. })
+        | Expression::UnaryOp(UnaryOp::Not(expression))
+        | Expression::Repeat(Repeat {
+            value: expression, ..
+        }) => visit(&expression.expression, f),
+        Expression::BinaryOp(
+            BinaryOp::And(lhs, rhs)
+            | BinaryOp::NAnd(lhs, rhs)
+            | BinaryOp::Or(lhs, rhs)
+            | BinaryOp::NOr(lhs, rhs)
+            | BinaryOp::XOr(lhs, rhs)
+            | BinaryOp::XNOr(lhs, rhs)
+            | BinaryOp::Multiplex(lhs, rhs),
+        )
+        | Expression::Drive(Drive {
+            value: lhs,
+            enable: rhs,
+        }) => {
+            visit(&lhs.expression, f);
+            visit(&rhs.expression, f);
+        }
+        Expression::Combine(Combine::Bits(bits)) => {
+            for bit in bits {
+                visit(&bit.expression, f);
+            }
+        }
+        Expression::Combine(Combine::Obj(values)) => {
+            for value in values.values() {
+                visit(value, f);
+            }
+        }
+        Expression::ModuleUse(module_use) => {
+            for argument in &module_use.arguments {
+                visit(&argument.value.expression, f);
+            }
+        }
+    }
+}
+
+// the name of the signal that is read, like `a`, `a.sum`, `a[2]` or `a.0..3`
+fn signal(expression: &Expression) -> Option<String> {
+    match expression {
+        Expression::Variable(name) => Some(name.clone()),
+        Expression::Extract(Extract {
+            expression,
+            extract,
+        }) => {
+            let signal = signal(&expression.expression)?;
+
+            Some(match extract {
+                ExtractInner::Bit(bit) => format!("{}.{}", signal, bit),
+                ExtractInner::Range(start, end) => format!("{}.{}..{}", signal, start, end),
+                ExtractInner::Name(key) => format!("{}.{}", signal, key),
+                ExtractInner::Element(index) => format!("{}[{}]", signal, index),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn is_constant(expression: &Expression) -> bool {
+    match expression {
+        Expression::Integer(_) => true,
+        Expression::Combine(Combine::Bits(bits)) => {
+            bits.iter().all(|bit| is_constant(&bit.expression))
+        }
+        Expression::Combine(Combine::Obj(values)) => values.values().all(is_constant),
+        Expression::Repeat(Repeat { value, .. }) => is_constant(&value.expression),
+        _ => false,
+    }
+}
+
+// if reading `read` reads a part of `name`, or all of it
+fn reads(read: &str, name: &str) -> bool {
+    let part_of = |part: &str, whole: &str| {
+        part.strip_prefix(whole)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+    };
+
+    part_of(read, name) || part_of(name, read)
+}
+
+fn gate(op: &BinaryOp) -> Option<&'static str> {
+    match op {
+        BinaryOp::And(..) => Some("an and gate"),
+        BinaryOp::NAnd(..) => Some("a nand gate"),
+        BinaryOp::Or(..) => Some("an or gate"),
+        BinaryOp::NOr(..) => Some("a nor gate"),
+        BinaryOp::XOr(..) => Some("a xor gate"),
+        BinaryOp::XNOr(..) => Some("a xnor gate"),
+        BinaryOp::Multiplex(..) => None,
+    }
+}
+
+fn definitions(statements: &[ProgramStatement]) -> impl Iterator<Item = &VariableDefinitions> {
+    statements.iter().filter_map(|statement| match statement {
+        ProgramStatement::VariableDefinitions(definitions) => Some(definitions),
+        _ => None,
+    })
+}
+
+struct Checker<'a> {
+    options: &'a LintOptions,
+    warnings: Vec<Warning>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, source: &SourceInfo, lint: &'static str, message: String) {
+        self.warnings
+            .extend(self.options.report(source, lint, message));
+    }
+
+    fn check_expression(&mut self, expression: &Expression, source: &SourceInfo) {
+        visit(expression, &mut |expression| {
+            let Expression::BinaryOp(op) = expression else {
+                return;
+            };
+
+            match op {
+                BinaryOp::Multiplex(values, selector) => {
+                    let (Expression::Combine(Combine::Bits(values)), KnownBitWidth::Fixed(width)) =
+                        (&values.expression, &selector.width)
+                    else {
+                        return;
+                    };
+
+                    let selectable = 1u64 << width.min(&32);
+
+                    if values.len() as u64 > selectable {
+                        self.report(
+                            source,
+                            "mux-range",
+                            format!(
+                                "A multiplexer has {} values, but its selector is {} bits wide, only the first {} can be selected",
+                                values.len(),
+                                width,
+                                selectable
+                            ),
+                        );
+                    }
+                }
+                BinaryOp::And(lhs, rhs)
+                | BinaryOp::NAnd(lhs, rhs)
+                | BinaryOp::Or(lhs, rhs)
+                | BinaryOp::NOr(lhs, rhs)
+                | BinaryOp::XOr(lhs, rhs)
+                | BinaryOp::XNOr(lhs, rhs) => {
+                    let Some(signal) = signal(&lhs.expression) else {
+                        return;
+                    };
+
+                    if Some(&signal) == self::signal(&rhs.expression).as_ref() {
+                        self.report(
+                            source,
+                            "same-inputs",
+                            format!("Both inputs of {} are {}", gate(op).unwrap(), signal),
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    // `read` are the names that are read somewhere else, like the globals
+    // that modules read
+    fn check_statements(&mut self, statements: &[ProgramStatement], read: &[String]) {
+        let mut names = read.to_vec();
+        let unknown = SourceInfo::default();
+
+        for statement in statements {
+            let expressions: Vec<_> = match statement {
+                ProgramStatement::VariableDefinitions(definitions) => definitions
+                    .definitions
+                    .iter()
+                    .filter_map(|definition| definition.value.as_ref())
+                    .map(|value| (&value.expression, &definitions.source))
+                    .collect(),
+                ProgramStatement::Expression(expression) => {
+                    vec![(expression, &unknown)]
+                }
+                _ => vec![],
+            };
+
+            for (expression, source) in expressions {
+                self.check_expression(expression, source);
+
+                visit(expression, &mut |expression| {
+                    if let Expression::Variable(name) = expression {
+                        names.push(name.clone());
+                    }
+                });
+            }
+        }
+
+        for definitions in self::definitions(statements) {
+            for definition in &definitions.definitions {
+                let name = &definition.name;
+
+                match (&definitions.decorator, &definition.value) {
+                    (Some(Decorator::In(..) | Decorator::Clock(_)), _)
+                        if !names.iter().any(|read| reads(read, name)) =>
+                    {
+                        self.report(
+                            &definitions.source,
+                            "unused-input",
+                            format!("The input {} is never used", name),
+                        );
+                    }
+                    (Some(Decorator::Out(..)), Some(value)) if is_constant(&value.expression) => {
+                        self.report(
+                            &definitions.source,
+                            "constant-output",
+                            format!("The output {} is always driven by a constant", name),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn check_module(&mut self, module: &Module, globals: &[&String]) {
+        if module.outputs.is_empty() && module.inouts.is_empty() {
+            self.report(
+                &module.source,
+                "no-outputs",
+                format!("Module {} has no outputs", module.name),
+            );
+        }
+
+        // ports are named by the module, wires can hide a global that was
+        // meant to be read with `@global`
+        let mut declared: Vec<&String> = vec![];
+
+        for definitions in definitions(&module.statements) {
+            for definition in &definitions.definitions {
+                let name = &definition.name;
+
+                // assigning a declared wire doesn't define anything
+                let assigned = definitions.decorator.is_none()
+                    && declared.iter().any(|wire| reads(name, wire));
+                let port = !matches!(definitions.decorator, None | Some(Decorator::Wire(_)));
+
+                declared.push(name);
+
+                if !assigned && !port && globals.contains(&name) {
+                    self.report(
+                        &definitions.source,
+                        "shadowed",
+                        format!(
+                            "{} in module {} has the name of a global",
+                            definition.name, module.name
+                        ),
+                    );
+                }
+            }
+        }
+
+        self.check_statements(&module.statements, &[]);
+    }
+}
+
+// the lints that look at the whole program once it is resolved
+pub fn check(program: &Program, options: &LintOptions) -> Vec<Warning> {
+    let mut checker = Checker {
+        options,
+        warnings: vec![],
+    };
+
+    let mut globals = vec![];
+    let mut global_reads = vec![];
+
+    for definitions in definitions(&program.statements) {
+        for definition in &definitions.definitions {
+            if !definition.name.starts_with('(') {
+                globals.push(&definition.name);
+            }
+        }
+    }
+
+    for statement in &program.statements {
+        let ProgramStatement::Module(module) = statement else {
+            continue;
+        };
+
+        checker.check_module(module, &globals);
+
+        for definitions in definitions(&module.statements) {
+            if let Some(Decorator::Global) = definitions.decorator {
+                global_reads.extend(definitions.definitions.iter().map(|d| d.name.clone()));
+            }
+        }
+    }
+
+    checker.check_statements(&program.statements, &global_reads);
+
+    checker.warnings
+}
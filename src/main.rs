@@ -1,40 +1,183 @@
 use std::{
     env,
     fs::File,
-    io::{Read, Write},
+    io::Write,
+    path::{Path, PathBuf},
+    process,
 };
 
-use digital::ToDigital;
-use parser::parse_program;
+use parser::{parse_file, ParserOptions};
+use resolver::ResolverOptions;
 
 mod digital;
+mod lint;
+mod netlist;
 mod parser;
+mod preprocessor;
+mod resolver;
 mod types;
 mod utils;
 
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} [lint | parse] <filename> [-D NAME[=VALUE]]... [-I DIRECTORY]... [--strict-widths] [--warn-widening] [--remove-dead-logic] [--enable LINT,...] [--disable LINT,...] [--lint-config FILE] [--format human|json]",
+        program
+    );
+    process::exit(1);
+}
+
+// looked for in the current directory if there is no --lint-config
+const LINT_CONFIG: &str = "dhdl-lint.conf";
+
+fn error(message: String) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1);
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Usage: {} <filename>", args[0]);
+
+    // `dhdl lint <filename>` only reports the warnings, without writing a
+    // circuit
+    let lint = args.get(1).is_some_and(|arg| arg == "lint");
+    // `dhdl parse <filename>` prints what the parser made of the file,
+    // without resolving any name or width
+    let parse = args.get(1).is_some_and(|arg| arg == "parse");
+
+    let mut filename = None;
+    let mut options = ParserOptions::default();
+    let mut resolver_options = ResolverOptions::default();
+    let mut lint_config = None;
+    // the lints turned on or off on the command line, in order
+    let mut lints = vec![];
+    let mut json = false;
+    let mut remove_dead_logic = false;
+
+    let mut i = if lint || parse { 2 } else { 1 };
+    while i < args.len() {
+        let arg = &args[i];
+
+        if arg == "--strict-widths" {
+            resolver_options.strict_widths = true;
+        } else if arg == "--warn-widening" {
+            resolver_options.warn_widening = true;
+        } else if arg == "--remove-dead-logic" {
+            remove_dead_logic = true;
+        } else if arg == "--enable" || arg == "--disable" {
+            i += 1;
+            let names = args.get(i).unwrap_or_else(|| usage(&args[0]));
+
+            for name in names.split(',') {
+                lints.push((name.trim().to_string(), arg == "--enable"));
+            }
+        } else if arg == "--lint-config" {
+            i += 1;
+            lint_config = Some(PathBuf::from(
+                args.get(i).unwrap_or_else(|| usage(&args[0])),
+            ));
+        } else if arg == "--format" && lint {
+            i += 1;
+            json = match args.get(i).map(|format| format.as_str()) {
+                Some("human") => false,
+                Some("json") => true,
+                _ => usage(&args[0]),
+            };
+        } else if let Some(define) = arg.strip_prefix("-D") {
+            // both `-D NAME` and `-DNAME` are accepted
+            let define = if define.is_empty() {
+                i += 1;
+                args.get(i).unwrap_or_else(|| usage(&args[0]))
+            } else {
+                define
+            };
+
+            let (name, value) = define.split_once('=').unwrap_or((define, ""));
+            options
+                .defines
+                .push((name.to_string(), value.to_string()));
+        } else if let Some(directory) = arg.strip_prefix("-I") {
+            let directory = if directory.is_empty() {
+                i += 1;
+                args.get(i).unwrap_or_else(|| usage(&args[0]))
+            } else {
+                directory
+            };
+
+            options.search_path.push(PathBuf::from(directory));
+        } else if filename.is_none() {
+            filename = Some(arg.clone());
+        } else {
+            usage(&args[0]);
+        }
+
+        i += 1;
+    }
+
+    let filename = filename.unwrap_or_else(|| usage(&args[0]));
+
+    // the command line overrides the config file
+    let lint_config = lint_config.or_else(|| {
+        let default = PathBuf::from(LINT_CONFIG);
+        default.exists().then_some(default)
+    });
+
+    if let Some(path) = lint_config {
+        resolver_options.lints.load(&path).unwrap_or_else(|e| error(e));
     }
 
-    let filename = &args[1];
+    for (name, enabled) in lints {
+        resolver_options
+            .lints
+            .set(&name, enabled)
+            .unwrap_or_else(|e| error(e));
+    }
+
+    // directories given on the command line are searched first
+    if let Some(paths) = env::var_os("DHDL_PATH") {
+        options.search_path.extend(env::split_paths(&paths));
+    }
 
     let input_file_path = format!("tests/{}.dhl", filename);
     let output_file_path = format!("output/{}.dig", filename);
 
-    let mut file = File::open(input_file_path).unwrap();
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).unwrap();
+    let ast = parse_file(Path::new(&input_file_path), options).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    });
+
+    if parse {
+        println!("{:#?}", ast);
+        return;
+    }
+
+    let lint_options = resolver_options.lints.clone();
+    let (ast, mut warnings) = resolver::resolve(ast, resolver_options);
+    warnings.extend(lint::check(&ast, &lint_options));
 
-    let input = contents.as_str();
+    if lint && json {
+        println!("{}", lint::to_json(&warnings));
+    } else {
+        for warning in &warnings {
+            eprintln!("warning: {} [{}]", warning, warning.lint);
+        }
+    }
 
-    let ast = parse_program(input).unwrap();
+    if lint {
+        process::exit(if warnings.is_empty() { 0 } else { 1 });
+    }
 
     println!("{:#?}", ast);
 
-    let mut circuit = digital::Circuit::new();
-    ast.convert_to_digital(&mut circuit);
+    let mut netlist = netlist::Netlist::from_program(&ast);
+    netlist.fold_constants();
+
+    if remove_dead_logic {
+        let removed = netlist.remove_dead_logic();
+        let plural = if removed == 1 { "" } else { "s" };
+        eprintln!("removed {} unused component{}", removed, plural);
+    }
+
+    let circuit = digital::Circuit::from_netlist(&netlist);
 
     let output = circuit.as_xml();
 
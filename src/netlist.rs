@@ -0,0 +1,726 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    sync::Arc,
+};
+
+use crate::{
+    parser::datatype::KnownBitWidth,
+    types::{
+        module::{ExternalModule, Module},
+        program::Program,
+    },
+    utils::{
+        indexed_name::{indexed_name, split_indexed_name},
+        integer_width::integer_width,
+    },
+};
+
+mod dead_logic;
+mod expression;
+mod fold;
+mod module;
+mod program;
+mod variable_definition;
+
+pub type NetId = usize;
+
+// a bundle of wires that always carry the same value, driven by one or more
+// cell outputs (more than one only through tri-state drivers)
+#[derive(Debug, Clone)]
+pub struct Net {
+    pub width: u32,
+    // the variable it was first assigned to, if any
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    In,
+    Out,
+    // both read and driven, like the data pins of a RAM
+    InOut,
+}
+
+#[derive(Debug, Clone)]
+pub struct Pin {
+    pub name: String,
+    pub direction: Direction,
+    pub net: NetId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GateOp {
+    And,
+    NAnd,
+    Or,
+    NOr,
+    XOr,
+    XNOr,
+}
+
+// what a cell computes. the widths are the widths of the nets it is
+// connected to.
+#[derive(Debug, Clone)]
+pub enum CellKind {
+    // `out` is always the value
+    Const(u64),
+    // `out` is `a` op `b`, bit by bit
+    Gate(GateOp),
+    Not,
+    // `out` is bits `from` to `to` of `in`
+    Slice(u32, u32),
+    // `out` is the pins `0`, `1`, ... one after the other, `0` in the lowest
+    // bits
+    Concat,
+    // `out` is the pin `0`, `1`, ... that `sel` picks
+    Multiplexer,
+    // `out` is `in` while `sel` is 1, high-Z otherwise
+    Driver,
+    // a pin of the circuit, `out` is an inout pin if it can be driven
+    Input {
+        label: String,
+        default: Option<u32>,
+    },
+    Clock {
+        label: String,
+        frequency: Option<u32>,
+    },
+    Output {
+        label: String,
+    },
+    // a component of Digital with the ports of the external module
+    External(Arc<ExternalModule>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Cell {
+    pub kind: CellKind,
+    pub pins: Vec<Pin>,
+}
+
+impl Cell {
+    pub fn pin(&self, name: &str) -> Option<NetId> {
+        self.pins
+            .iter()
+            .find(|pin| pin.name == name)
+            .map(|pin| pin.net)
+    }
+
+    // the pins the cell reads
+    pub fn inputs(&self) -> impl Iterator<Item = &Pin> {
+        self.pins
+            .iter()
+            .filter(|pin| pin.direction != Direction::Out)
+    }
+
+    // the pins the cell drives
+    pub fn outputs(&self) -> impl Iterator<Item = &Pin> {
+        self.pins
+            .iter()
+            .filter(|pin| pin.direction != Direction::In)
+    }
+}
+
+// the circuit as cells connected by nets, without any geometry. it is built
+// from the resolved program, backends like Digital only look at this.
+#[derive(Debug, Default)]
+pub struct Netlist {
+    pub nets: Vec<Net>,
+    pub cells: Vec<Cell>,
+}
+
+fn pin(name: &str, direction: Direction, net: NetId) -> Pin {
+    Pin {
+        name: name.to_string(),
+        direction,
+        net,
+    }
+}
+
+impl Netlist {
+    pub fn from_program(program: &Program) -> Self {
+        let mut builder = NetlistBuilder::new();
+        program.to_netlist(&mut builder);
+        builder.finish()
+    }
+
+    pub fn width(&self, net: NetId) -> u32 {
+        self.nets[net].width
+    }
+
+    pub fn add_net(&mut self, width: u32) -> NetId {
+        self.nets.push(Net { width, name: None });
+        self.nets.len() - 1
+    }
+
+    pub fn add_cell(&mut self, kind: CellKind, pins: Vec<Pin>) {
+        self.cells.push(Cell { kind, pins });
+    }
+
+    pub fn constant(&mut self, value: u64, width: u32) -> NetId {
+        let out = self.add_net(width);
+        self.add_cell(
+            CellKind::Const(value),
+            vec![pin("out", Direction::Out, out)],
+        );
+        out
+    }
+
+    pub fn gate(&mut self, op: GateOp, a: NetId, b: NetId) -> NetId {
+        let out = self.add_net(self.width(a));
+        self.add_cell(
+            CellKind::Gate(op),
+            vec![
+                pin("a", Direction::In, a),
+                pin("b", Direction::In, b),
+                pin("out", Direction::Out, out),
+            ],
+        );
+        out
+    }
+
+    pub fn not(&mut self, input: NetId) -> NetId {
+        let out = self.add_net(self.width(input));
+        self.add_cell(
+            CellKind::Not,
+            vec![
+                pin("in", Direction::In, input),
+                pin("out", Direction::Out, out),
+            ],
+        );
+        out
+    }
+
+    pub fn slice(&mut self, input: NetId, from: u32, to: u32) -> NetId {
+        let out = self.add_net(1 + to - from);
+        self.add_cell(
+            CellKind::Slice(from, to),
+            vec![
+                pin("in", Direction::In, input),
+                pin("out", Direction::Out, out),
+            ],
+        );
+        out
+    }
+
+    pub fn concat(&mut self, parts: &[NetId]) -> NetId {
+        let out = self.add_net(parts.iter().map(|part| self.width(*part)).sum());
+
+        let mut pins: Vec<_> = parts
+            .iter()
+            .enumerate()
+            .map(|(index, part)| pin(&index.to_string(), Direction::In, *part))
+            .collect();
+        pins.push(pin("out", Direction::Out, out));
+
+        self.add_cell(CellKind::Concat, pins);
+        out
+    }
+
+    pub fn multiplexer(&mut self, selector: NetId, values: &[NetId]) -> NetId {
+        let out = self.add_net(self.width(values[0]));
+
+        let mut pins: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| pin(&index.to_string(), Direction::In, *value))
+            .collect();
+        pins.push(pin("sel", Direction::In, selector));
+        pins.push(pin("out", Direction::Out, out));
+
+        self.add_cell(CellKind::Multiplexer, pins);
+        out
+    }
+
+    pub fn driver(&mut self, input: NetId, enable: NetId) -> NetId {
+        let out = self.add_net(self.width(input));
+        self.add_cell(
+            CellKind::Driver,
+            vec![
+                pin("in", Direction::In, input),
+                pin("sel", Direction::In, enable),
+                pin("out", Direction::Out, out),
+            ],
+        );
+        out
+    }
+
+    // the cell that drives each net, none if zero or several cells do
+    pub fn drivers(&self) -> Vec<Option<usize>> {
+        let mut drivers = vec![None; self.nets.len()];
+        let mut shared = vec![false; self.nets.len()];
+
+        for (index, cell) in self.cells.iter().enumerate() {
+            for pin in cell.outputs() {
+                shared[pin.net] |= drivers[pin.net].is_some();
+                drivers[pin.net] = Some(index);
+            }
+        }
+
+        drivers
+            .into_iter()
+            .zip(shared)
+            .map(|(driver, shared)| driver.filter(|_| !shared))
+            .collect()
+    }
+
+    // how many pins read each net
+    pub fn readers(&self) -> Vec<usize> {
+        let mut readers = vec![0; self.nets.len()];
+
+        for pin in self.cells.iter().flat_map(|cell| cell.inputs()) {
+            readers[pin.net] += 1;
+        }
+
+        readers
+    }
+
+    // every pin on `from` is moved to `to`, which keeps its name if it has one
+    pub fn replace_net(&mut self, from: NetId, to: NetId) {
+        for pin in self.cells.iter_mut().flat_map(|cell| &mut cell.pins) {
+            if pin.net == from {
+                pin.net = to;
+            }
+        }
+
+        if self.nets[to].name.is_none() {
+            self.nets[to].name = self.nets[from].name.take();
+        }
+    }
+
+    // drops the nets that no pin is connected to anymore
+    pub fn remove_unused_nets(&mut self) {
+        let mut ids = vec![None; self.nets.len()];
+        let mut nets = vec![];
+
+        for pin in self.cells.iter_mut().flat_map(|cell| &mut cell.pins) {
+            pin.net = *ids[pin.net].get_or_insert_with(|| {
+                nets.push(self.nets[pin.net].clone());
+                nets.len() - 1
+            });
+        }
+
+        self.nets = nets;
+    }
+}
+
+// a value while the netlist is built, structured like its width
+#[derive(Debug, Clone)]
+pub enum Value {
+    Empty,
+    Net(NetId),
+    Object(HashMap<String, Arc<Value>>),
+    Array(Vec<Arc<Value>>),
+}
+
+impl Value {
+    // an object with a single field stands for that field
+    pub fn net(&self) -> NetId {
+        match self {
+            Value::Empty => panic!("Empty data has no size"),
+            Value::Net(net) => *net,
+            Value::Object(map) => {
+                if map.keys().len() != 1 {
+                    panic!("! Object width has more than one key");
+                }
+
+                map.values().next().unwrap().as_ref().net()
+            }
+            Value::Array(_) => panic!("Array data has no size"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NetlistVariable {
+    name: String,
+    value: Value,
+    // a declared wire that can still be driven
+    undefined: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum NetlistModule {
+    Internal(Module),
+    External(Arc<ExternalModule>),
+}
+
+impl NetlistModule {
+    pub fn get_name(&self) -> String {
+        match self {
+            NetlistModule::Internal(module) => module.name.clone(),
+            NetlistModule::External(module) => module.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CurrentModule {
+    variables: Vec<NetlistVariable>,
+}
+
+impl CurrentModule {
+    fn find(&self, name: &str) -> Option<&NetlistVariable> {
+        self.variables.iter().find(|variable| variable.name == name)
+    }
+
+    // like in the resolver, `out[3]` is also element 3 of the array `out`,
+    // and `out` is also the array of `out[0]`, `out[1]`, ...
+    fn find_variable(&self, name: String) -> Option<NetlistVariable> {
+        if let Some(variable) = self.find(&name) {
+            return Some(variable.clone());
+        }
+
+        if let Some((array, index)) = split_indexed_name(&name) {
+            if let Some(variable) = self.find_variable(array.to_string()) {
+                if let Value::Array(elements) = &variable.value {
+                    // `out[1]` isn't part of `out` yet while it is being defined
+                    if let Some(element) = elements.get(index as usize) {
+                        return Some(NetlistVariable {
+                            name,
+                            value: element.as_ref().clone(),
+                            undefined: variable.undefined,
+                        });
+                    }
+                }
+            }
+        }
+
+        let elements: Vec<_> = (0..)
+            .map_while(|index| {
+                self.find(&indexed_name(&name, index))
+                    .map(|element| Arc::new(element.value.clone()))
+            })
+            .collect();
+
+        if elements.is_empty() {
+            None
+        } else {
+            Some(NetlistVariable {
+                name,
+                value: Value::Array(elements),
+                undefined: false,
+            })
+        }
+    }
+}
+
+// a cell that only computes its output from its inputs. cells with the same
+// structure have the same output, so they are only built once.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Structure {
+    Const(u64, u32),
+    Gate(GateOp, NetId, NetId),
+    Not(NetId),
+    Slice(NetId, u32, u32),
+    Concat(Vec<NetId>),
+    Multiplexer(NetId, Vec<NetId>),
+}
+
+// turns the resolved program into a netlist. every use of a module gets a
+// copy of the cells of the module.
+pub struct NetlistBuilder {
+    netlist: Netlist,
+    // nets that were connected to each other, see `connect`
+    merged: Vec<NetId>,
+    // the output of every cell that was built, by its structure, and the
+    // other way around
+    structures: HashMap<Structure, NetId>,
+    built: HashMap<NetId, Structure>,
+    // shared cells assigned to declared wires, they are connected once it is
+    // known which wires are buses, see `assign`
+    assignments: Vec<(NetId, NetId)>,
+
+    modules: Vec<NetlistModule>,
+    current_module: Vec<CurrentModule>,
+}
+
+pub trait ToNetlist {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value;
+}
+
+impl NetlistBuilder {
+    fn new() -> Self {
+        Self {
+            netlist: Netlist::default(),
+            merged: vec![],
+            structures: HashMap::new(),
+            built: HashMap::new(),
+            assignments: vec![],
+
+            modules: vec![],
+            current_module: vec![CurrentModule { variables: vec![] }],
+        }
+    }
+
+    pub fn is_top(&self) -> bool {
+        self.current_module.len() <= 1
+    }
+
+    pub fn add_variable(&mut self, variable: NetlistVariable) {
+        self.name_nets(&variable.name, &variable.value);
+
+        self.current_module
+            .last_mut()
+            .unwrap()
+            .variables
+            .push(variable);
+    }
+
+    // nets are named after the first variable they are assigned to, fields
+    // and elements get the name of the variable with the field or index
+    fn name_nets(&mut self, name: &str, value: &Value) {
+        match value {
+            Value::Empty => {}
+            Value::Net(net) => {
+                let net = &mut self.netlist.nets[*net];
+                net.name.get_or_insert_with(|| name.to_string());
+            }
+            Value::Object(fields) => {
+                for (field, value) in fields {
+                    self.name_nets(&format!("{}.{}", name, field), value);
+                }
+            }
+            Value::Array(elements) => {
+                for (index, value) in elements.iter().enumerate() {
+                    self.name_nets(&indexed_name(name, index as u32), value);
+                }
+            }
+        }
+    }
+
+    // a module only sees its own variables
+    pub fn find_variable(&self, name: String) -> Option<NetlistVariable> {
+        self.current_module.last().unwrap().find_variable(name)
+    }
+
+    // a variable of the top level, declared with `@global` inside a module
+    pub fn find_global(&self, name: String) -> Option<NetlistVariable> {
+        self.current_module[0].find_variable(name)
+    }
+
+    pub fn find_module(&self, name: &str) -> Option<&NetlistModule> {
+        self.modules.iter().find(|module| module.get_name() == name)
+    }
+
+    pub fn width(&self, net: NetId) -> u32 {
+        self.netlist.width(net)
+    }
+
+    // the output of a cell with this structure, built unless it was built
+    // before
+    fn structure(&mut self, structure: Structure) -> NetId {
+        if let Some(net) = self.structures.get(&structure) {
+            return *net;
+        }
+
+        let net = self.build(&structure);
+        self.built.insert(net, structure.clone());
+        self.structures.insert(structure, net);
+        net
+    }
+
+    fn build(&mut self, structure: &Structure) -> NetId {
+        match structure {
+            Structure::Const(value, width) => self.netlist.constant(*value, *width),
+            Structure::Gate(op, a, b) => self.netlist.gate(*op, *a, *b),
+            Structure::Not(input) => self.netlist.not(*input),
+            Structure::Slice(input, from, to) => self.netlist.slice(*input, *from, *to),
+            Structure::Concat(parts) => self.netlist.concat(parts),
+            Structure::Multiplexer(selector, values) => self.netlist.multiplexer(*selector, values),
+        }
+    }
+
+    // a net that becomes part of a bus, which an inout or a driver can put
+    // other values on. a shared cell would pass them on to everything else
+    // that reads it, so the bus gets a copy of its own.
+    pub fn unshared(&mut self, net: NetId) -> NetId {
+        match self.built.get(&net).cloned() {
+            Some(structure) => self.build(&structure),
+            None => net,
+        }
+    }
+
+    pub fn constant(&mut self, value: u64, width: u32) -> NetId {
+        self.structure(Structure::Const(value, width))
+    }
+
+    pub fn gate(&mut self, op: GateOp, a: NetId, b: NetId) -> NetId {
+        // every gate is commutative
+        let (a, b) = (a.min(b), a.max(b));
+
+        self.structure(Structure::Gate(op, a, b))
+    }
+
+    pub fn not(&mut self, input: NetId) -> NetId {
+        self.structure(Structure::Not(input))
+    }
+
+    pub fn slice(&mut self, input: NetId, from: u32, to: u32) -> NetId {
+        self.structure(Structure::Slice(input, from, to))
+    }
+
+    pub fn concat(&mut self, parts: &[NetId]) -> NetId {
+        self.structure(Structure::Concat(parts.to_vec()))
+    }
+
+    pub fn multiplexer(&mut self, selector: NetId, values: &[NetId]) -> NetId {
+        self.structure(Structure::Multiplexer(selector, values.to_vec()))
+    }
+
+    pub fn integer(&mut self, value: u32) -> NetId {
+        self.constant(value.into(), integer_width(value))
+    }
+
+    // a value of `to` bits, cut off or extended with zeros
+    pub fn cast(&mut self, net: NetId, to: u32) -> NetId {
+        let from = self.width(net);
+
+        if to == 0 {
+            panic!("A value of {} bits can't be cast to 0 bits", from);
+        }
+
+        if from > to {
+            self.slice(net, 0, to - 1)
+        } else if from < to {
+            let zeros = self.constant(0, to - from);
+            self.concat(&[net, zeros])
+        } else {
+            net
+        }
+    }
+
+    // casts a single value to the width of `to`
+    pub fn cast_value(&mut self, value: Value, to: &KnownBitWidth) -> NetId {
+        self.cast(value.net(), to.get_size())
+    }
+
+    // casts every field of an object (or element of an array) to the width
+    // of the matching bundle field
+    pub fn cast_object(&mut self, value: Value, to: &KnownBitWidth) -> Value {
+        match (value, to) {
+            (Value::Object(fields), KnownBitWidth::Object(widths)) => Value::Object(
+                widths
+                    .iter()
+                    .map(|(key, width)| {
+                        let field = fields
+                            .get(key)
+                            .unwrap_or_else(|| panic!("Object does not have key {}", key))
+                            .as_ref()
+                            .clone();
+
+                        (key.clone(), Arc::new(self.cast_object(field, width)))
+                    })
+                    .collect(),
+            ),
+            (Value::Array(elements), KnownBitWidth::Array(widths)) => Value::Array(
+                elements
+                    .iter()
+                    .zip(widths)
+                    .map(|(element, width)| {
+                        Arc::new(self.cast_object(element.as_ref().clone(), width))
+                    })
+                    .collect(),
+            ),
+            (value, to) => Value::Net(self.cast_value(value, to)),
+        }
+    }
+
+    // the two nets are the same from now on
+    pub fn connect(&mut self, a: NetId, b: NetId) {
+        let (a, b) = (self.representative(a), self.representative(b));
+
+        if a != b {
+            self.merged.resize(self.netlist.nets.len(), usize::MAX);
+            self.merged[b] = a;
+        }
+    }
+
+    // connects a value to a declared wire. a wire only turns out to be a bus
+    // once all of its drivers and inouts are known, so a shared cell is
+    // connected at the end
+    pub fn assign(&mut self, value: NetId, wire: NetId) {
+        if self.built.contains_key(&value) {
+            self.assignments.push((value, wire));
+        } else {
+            self.connect(value, wire);
+        }
+    }
+
+    // a wire with an inout pin or a driver on it is a bus, the shared cells
+    // assigned to it are copied
+    fn connect_assignments(&mut self) {
+        let mut buses = HashSet::new();
+
+        for cell in &self.netlist.cells {
+            for pin in &cell.pins {
+                let driven =
+                    matches!(cell.kind, CellKind::Driver) && pin.direction == Direction::Out;
+
+                if pin.direction == Direction::InOut || driven {
+                    buses.insert(self.representative(pin.net));
+                }
+            }
+        }
+
+        for (value, wire) in mem::take(&mut self.assignments) {
+            let value = if buses.contains(&self.representative(wire)) {
+                self.unshared(value)
+            } else {
+                value
+            };
+
+            self.connect(value, wire);
+        }
+    }
+
+    fn representative(&self, mut net: NetId) -> NetId {
+        while let Some(&next) = self.merged.get(net).filter(|next| **next != usize::MAX) {
+            net = next;
+        }
+
+        net
+    }
+
+    // replaces every connected net with a single one
+    fn finish(mut self) -> Netlist {
+        self.connect_assignments();
+
+        let mut ids = HashMap::new();
+        let mut nets = vec![];
+
+        for net in 0..self.netlist.nets.len() {
+            let representative = self.representative(net);
+
+            let id = *ids.entry(representative).or_insert_with(|| {
+                nets.push(self.netlist.nets[representative].clone());
+                nets.len() - 1
+            });
+
+            // the first name of any of the connected nets
+            if nets[id].name.is_none() {
+                nets[id].name = self.netlist.nets[net].name.clone();
+            }
+        }
+
+        let ids: Vec<_> = (0..self.netlist.nets.len())
+            .map(|net| ids[&self.representative(net)])
+            .collect();
+
+        let cells = self
+            .netlist
+            .cells
+            .into_iter()
+            .map(|mut cell| {
+                for pin in &mut cell.pins {
+                    pin.net = ids[pin.net];
+                }
+
+                cell
+            })
+            .collect();
+
+        Netlist { nets, cells }
+    }
+}
@@ -0,0 +1,58 @@
+use super::{Cell, CellKind, Netlist};
+
+// the cells that are always kept: the pins of the circuit, and external
+// modules, which may store what they read
+fn is_root(cell: &Cell) -> bool {
+    matches!(
+        cell.kind,
+        CellKind::Input { .. }
+            | CellKind::Clock { .. }
+            | CellKind::Output { .. }
+            | CellKind::External(_)
+    )
+}
+
+impl Netlist {
+    // removes every cell whose outputs never reach an output of the circuit or
+    // an external module, returns how many were removed
+    pub fn remove_dead_logic(&mut self) -> usize {
+        let mut drivers = vec![vec![]; self.nets.len()];
+
+        for (index, cell) in self.cells.iter().enumerate() {
+            for pin in cell.outputs() {
+                drivers[pin.net].push(index);
+            }
+        }
+
+        let mut live: Vec<_> = self.cells.iter().map(is_root).collect();
+        let mut live_nets = vec![false; self.nets.len()];
+
+        let mut stack: Vec<_> = (0..self.cells.len()).filter(|index| live[*index]).collect();
+
+        // everything that drives a net a live cell reads is live as well
+        while let Some(index) = stack.pop() {
+            for pin in self.cells[index].inputs() {
+                if live_nets[pin.net] {
+                    continue;
+                }
+
+                live_nets[pin.net] = true;
+
+                for driver in &drivers[pin.net] {
+                    if !live[*driver] {
+                        live[*driver] = true;
+                        stack.push(*driver);
+                    }
+                }
+            }
+        }
+
+        let count = self.cells.len();
+
+        let mut live = live.into_iter();
+        self.cells.retain(|_| live.next().unwrap());
+        self.remove_unused_nets();
+
+        count - self.cells.len()
+    }
+}
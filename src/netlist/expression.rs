@@ -0,0 +1,413 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    parser::datatype::KnownBitWidth,
+    types::expression::{
+        BinaryOp, Combine, Drive, Expression, ExpressionWithWidth, Extract, ExtractInner,
+        ModuleUse, Repeat, UnaryOp,
+    },
+    utils::integer_width::integer_width,
+};
+
+use super::{
+    pin, CellKind, CurrentModule, Direction, GateOp, NetlistBuilder, NetlistModule,
+    NetlistVariable, ToNetlist, Value,
+};
+
+impl ToNetlist for ExpressionWithWidth {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        self.expression.to_netlist(builder)
+    }
+}
+
+impl ToNetlist for Expression {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        match self {
+            Expression::Integer(value) => Value::Net(builder.integer(*value)),
+            Expression::Variable(variable) => {
+                let var = builder.find_variable((*variable).clone());
+
+                match var {
+                    Some(var) => var.value,
+                    None => panic!("Variable {} not found", variable),
+                }
+            }
+            Expression::UnaryOp(op) => op.to_netlist(builder),
+            Expression::BinaryOp(op) => op.to_netlist(builder),
+            Expression::Extract(extract) => extract.to_netlist(builder),
+            Expression::Combine(combine) => combine.to_netlist(builder),
+            Expression::ModuleUse(module_use) => module_use.to_netlist(builder),
+            Expression::Drive(drive) => drive.to_netlist(builder),
+            Expression::Repeat(repeat) => repeat.to_netlist(builder),
+            Expression::String(_) => panic!("Unexpected string"),
+        }
+    }
+}
+
+fn gate_op(op: &BinaryOp) -> Option<(GateOp, &ExpressionWithWidth, &ExpressionWithWidth)> {
+    let (op, lhs, rhs) = match op {
+        BinaryOp::And(lhs, rhs) => (GateOp::And, lhs, rhs),
+        BinaryOp::NAnd(lhs, rhs) => (GateOp::NAnd, lhs, rhs),
+        BinaryOp::Or(lhs, rhs) => (GateOp::Or, lhs, rhs),
+        BinaryOp::NOr(lhs, rhs) => (GateOp::NOr, lhs, rhs),
+        BinaryOp::XOr(lhs, rhs) => (GateOp::XOr, lhs, rhs),
+        BinaryOp::XNOr(lhs, rhs) => (GateOp::XNOr, lhs, rhs),
+        BinaryOp::Multiplex(..) => return None,
+    };
+
+    Some((op, lhs.as_ref(), rhs.as_ref()))
+}
+
+impl ToNetlist for BinaryOp {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        let Some((op, lhs, rhs)) = gate_op(self) else {
+            let BinaryOp::Multiplex(lhs, rhs) = self else {
+                unreachable!()
+            };
+
+            // we do not trust the width of the lhs, since the Combine is
+            // not really made for this use case
+            let Expression::Combine(Combine::Bits(lhs)) = &lhs.as_ref().expression else {
+                panic!("Multiplexer lhs must be a Combine");
+            };
+
+            let selector_bits = integer_width(lhs.len() as u32);
+            let max_size = lhs
+                .iter()
+                .map(|expr| {
+                    if let KnownBitWidth::Fixed(width) = expr.width {
+                        width
+                    } else {
+                        panic!("Unknown bit width at multiplexer lhs")
+                    }
+                })
+                .max()
+                .unwrap_or_else(|| panic!("Multiplexer lhs is empty"));
+
+            let selector = rhs.to_netlist(builder);
+            let selector = builder.cast_value(selector, &KnownBitWidth::Fixed(selector_bits));
+
+            // the selector can pick values that don't exist, they are 0
+            let values: Vec<_> = (0..(1 << selector_bits))
+                .map(|i| {
+                    let value = match lhs.get(i) {
+                        Some(expr) => expr.to_netlist(builder).net(),
+                        None => builder.constant(0, 1),
+                    };
+
+                    builder.cast(value, max_size)
+                })
+                .collect();
+
+            return Value::Net(builder.multiplexer(selector, &values));
+        };
+
+        let lhs_value = lhs.to_netlist(builder);
+        let rhs_value = rhs.to_netlist(builder);
+
+        let largest_type = KnownBitWidth::max(lhs.width.clone(), rhs.width.clone());
+
+        if !matches!(largest_type, KnownBitWidth::Fixed(_)) {
+            panic!("Trying to perform binary operation on object variables");
+        }
+
+        let lhs_casted = builder.cast_value(lhs_value, &largest_type);
+        let rhs_casted = builder.cast_value(rhs_value, &largest_type);
+
+        Value::Net(builder.gate(op, lhs_casted, rhs_casted))
+    }
+}
+
+impl ToNetlist for UnaryOp {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        match self {
+            UnaryOp::Not(expression) => {
+                if !matches!(expression.width, KnownBitWidth::Fixed(_)) {
+                    panic!("Trying to perform unary operation on object variables");
+                }
+
+                let input = expression.to_netlist(builder).net();
+
+                Value::Net(builder.not(input))
+            }
+        }
+    }
+}
+
+impl ToNetlist for Combine {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        match self {
+            Combine::Bits(values) => {
+                // every value is a single bit
+                let bits: Vec<_> = values
+                    .iter()
+                    .map(|value| {
+                        let bit = value.to_netlist(builder);
+                        builder.cast_value(bit, &KnownBitWidth::Fixed(1))
+                    })
+                    .collect();
+
+                Value::Net(builder.concat(&bits))
+            }
+            Combine::Obj(map) => {
+                let mut obj = HashMap::new();
+
+                for (key, value) in map {
+                    obj.insert(key.clone(), Arc::new(value.to_netlist(builder)));
+                }
+
+                Value::Object(obj)
+            }
+        }
+    }
+}
+
+impl ToNetlist for Extract {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        match &self.extract {
+            ExtractInner::Bit(bit) => {
+                let input = self.expression.to_netlist(builder);
+
+                let KnownBitWidth::Fixed(bit_width) = self.expression.width else {
+                    panic!("Extracting a bit from an object variable")
+                };
+
+                // bits past the end are 0
+                if *bit >= bit_width {
+                    Value::Net(builder.constant(0, 1))
+                } else {
+                    Value::Net(builder.slice(input.net(), *bit, *bit))
+                }
+            }
+            ExtractInner::Range(from, to) => {
+                let input = self.expression.to_netlist(builder);
+                let input_casted = builder.cast_value(input, &KnownBitWidth::Fixed(to + 1));
+
+                Value::Net(builder.slice(input_casted, *from, *to))
+            }
+            ExtractInner::Name(name) => {
+                let input = self.expression.to_netlist(builder);
+
+                if let Value::Object(obj) = input {
+                    if let Some(value) = obj.get(name) {
+                        value.as_ref().clone()
+                    } else {
+                        panic!("Object does not have key {}", name);
+                    }
+                } else {
+                    panic!("Extracting a key from a non-object variable");
+                }
+            }
+            ExtractInner::Element(index) => {
+                let input = self.expression.to_netlist(builder);
+
+                if let Value::Array(elements) = input {
+                    elements[*index as usize].as_ref().clone()
+                } else {
+                    panic!("Indexing a non-array variable");
+                }
+            }
+        }
+    }
+}
+
+impl ToNetlist for ModuleUse {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        let module = builder
+            .find_module(&self.name)
+            .cloned()
+            .unwrap_or_else(|| panic!("Module {} not found", self.name));
+
+        match module {
+            NetlistModule::Internal(module) => {
+                // the arguments are converted where the module is used, the
+                // module itself only sees its ports
+                let mut ports = vec![];
+
+                for input in module.inputs {
+                    let argument = self.argument(&input.name);
+
+                    // the resolver makes sure that every input that isn't
+                    // connected has a default
+                    let Some(argument) = argument else {
+                        let default = input.default.unwrap_or_else(|| {
+                            panic!("Input {} not found", input.name);
+                        });
+
+                        let value = Value::Net(builder.integer(default));
+                        let net = builder.cast_value(value, &input.width);
+
+                        ports.push(NetlistVariable {
+                            name: input.name.clone(),
+                            value: Value::Net(net),
+                            undefined: false,
+                        });
+                        continue;
+                    };
+
+                    let input_value = argument.value.to_netlist(builder);
+
+                    // inside the module an input always has the width it is
+                    // declared with, whatever it is connected to
+                    let value = if input.width.is_structured() {
+                        builder.cast_object(input_value, &input.width)
+                    } else {
+                        Value::Net(builder.cast_value(input_value, &input.width))
+                    };
+
+                    ports.push(NetlistVariable {
+                        name: input.name.clone(),
+                        value,
+                        undefined: false,
+                    });
+                }
+
+                // a bidirectional port is the wire it is connected to, the
+                // module can drive it just like a @wire
+                for inout in module.inouts.iter() {
+                    let argument = self.argument(&inout.name).unwrap_or_else(|| {
+                        panic!("Bidirectional port {} is not connected", inout.name);
+                    });
+
+                    // an expression connected to the port becomes a bus
+                    // itself
+                    let net = argument.value.to_netlist(builder).net();
+
+                    ports.push(NetlistVariable {
+                        name: inout.name.clone(),
+                        value: Value::Net(builder.unshared(net)),
+                        undefined: true,
+                    });
+                }
+
+                builder
+                    .current_module
+                    .push(CurrentModule { variables: ports });
+
+                for statement in &module.statements {
+                    statement.to_netlist(builder);
+                }
+
+                let output_variables = module
+                    .outputs
+                    .iter()
+                    .chain(module.inouts.iter())
+                    .map(|v| v.name.clone());
+
+                let mut map = HashMap::new();
+
+                for var in output_variables {
+                    let var = builder
+                        .current_module
+                        .last()
+                        .unwrap()
+                        .variables
+                        .iter()
+                        .find(|v| v.name == var); // not using find_variable here for a reason
+                    if let Some(var) = var {
+                        map.insert(var.name.clone(), Arc::new(var.value.clone()));
+                    } else {
+                        panic!("Output variable not found");
+                    }
+                }
+
+                builder.current_module.pop();
+
+                Value::Object(map)
+            }
+
+            NetlistModule::External(module) => {
+                let mut pins = vec![];
+
+                for argument in self.arguments.iter() {
+                    // the resolver has named every argument
+                    let key = argument.name.as_ref().unwrap();
+                    let value = argument.value.to_netlist(builder);
+
+                    let (port, direction) = module
+                        .inputs
+                        .iter()
+                        .map(|port| (port, Direction::In))
+                        .chain(module.inouts.iter().map(|port| (port, Direction::InOut)))
+                        .find(|(port, _)| port.name == *key)
+                        .unwrap_or_else(|| {
+                            panic!("Module argument {} not found for module {}", key, self.name)
+                        });
+
+                    // the resolver makes sure that an inout has the width of
+                    // its port, it is connected to the wire itself. an
+                    // expression connected to it becomes a bus of its own.
+                    let net = match direction {
+                        Direction::InOut => builder.unshared(value.net()),
+                        _ => builder.cast_value(value, &port.width),
+                    };
+                    pins.push(pin(key, direction, net));
+                }
+
+                // inputs that aren't connected are tied to their default
+                for input in module.inputs.iter() {
+                    if self.argument(&input.name).is_some() {
+                        continue;
+                    }
+
+                    let Some(default) = input.default else {
+                        continue;
+                    };
+
+                    let value = Value::Net(builder.integer(default));
+                    let net = builder.cast_value(value, &input.width);
+                    pins.push(pin(&input.name, Direction::In, net));
+                }
+
+                let mut map = HashMap::new();
+
+                for output in module.outputs.iter() {
+                    let net = builder.netlist.add_net(output.width.get_size());
+                    pins.push(pin(&output.name, Direction::Out, net));
+                    map.insert(output.name.clone(), Arc::new(Value::Net(net)));
+                }
+
+                // an inout reads the wire it is connected to
+                for inout in module.inouts.iter() {
+                    let net = match pins.iter().find(|pin| pin.name == inout.name) {
+                        Some(pin) => pin.net,
+                        None => {
+                            let net = builder.netlist.add_net(inout.width.get_size());
+                            pins.push(pin(&inout.name, Direction::InOut, net));
+                            net
+                        }
+                    };
+
+                    map.insert(inout.name.clone(), Arc::new(Value::Net(net)));
+                }
+
+                builder.netlist.add_cell(CellKind::External(module), pins);
+
+                Value::Object(map)
+            }
+        }
+    }
+}
+
+impl ToNetlist for Drive {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        if !matches!(self.value.width, KnownBitWidth::Fixed(_)) {
+            panic!("Trying to drive an object variable");
+        }
+
+        let value = self.value.to_netlist(builder).net();
+        let enable = self.enable.to_netlist(builder).net();
+
+        Value::Net(builder.netlist.driver(value, enable))
+    }
+}
+
+impl ToNetlist for Repeat {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        Value::Array(
+            (0..self.count)
+                .map(|_| Arc::new(self.value.to_netlist(builder)))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::HashSet;
+
+use super::{pin, Cell, CellKind, Direction, GateOp, NetId, Netlist};
+
+// every bit of a net of `width` bits
+fn mask(width: u32) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+fn gate(op: GateOp, a: u64, b: u64) -> u64 {
+    match op {
+        GateOp::And => a & b,
+        GateOp::NAnd => !(a & b),
+        GateOp::Or => a | b,
+        GateOp::NOr => !(a | b),
+        GateOp::XOr => a ^ b,
+        GateOp::XNOr => !(a ^ b),
+    }
+}
+
+#[derive(Debug)]
+enum Part {
+    Net(NetId),
+    // a value and its width
+    Constant(u64, u32),
+}
+
+// what a cell can be replaced with
+#[derive(Debug)]
+enum Folded {
+    Constant(u64),
+    // the cell passes this net through unchanged
+    Net(NetId),
+    Not(NetId),
+    Slice(NetId, u32, u32),
+    Concat(Vec<Part>),
+}
+
+// a bitwise function of a single net, given what it outputs when all bits of
+// the net are 0 and when they are all 1
+fn bitwise(zeros: u64, ones: u64, net: NetId, all: u64) -> Option<Folded> {
+    let (zeros, ones) = (zeros & all, ones & all);
+
+    if zeros == ones {
+        Some(Folded::Constant(zeros))
+    } else if zeros == 0 && ones == all {
+        Some(Folded::Net(net))
+    } else if zeros == all && ones == 0 {
+        Some(Folded::Not(net))
+    } else {
+        None
+    }
+}
+
+// the cells that only compute a value, they can go once nothing reads it
+fn is_pure(cell: &Cell) -> bool {
+    matches!(
+        cell.kind,
+        CellKind::Const(_)
+            | CellKind::Gate(_)
+            | CellKind::Not
+            | CellKind::Slice(..)
+            | CellKind::Concat
+            | CellKind::Multiplexer
+    )
+}
+
+impl Netlist {
+    // replaces every cell with a constant output by a `Const`, and simplifies
+    // gates with a constant or twice the same input, multiplexers with a
+    // constant selector and nested slices and concatenations. the cells that
+    // were only read by the replaced ones are removed.
+    pub fn fold_constants(&mut self) {
+        let mut orphaned = HashSet::new();
+
+        while self.fold_once(&mut orphaned) {}
+
+        self.remove_orphans(orphaned);
+        self.remove_unused_nets();
+    }
+
+    fn fold_once(&mut self, orphaned: &mut HashSet<NetId>) -> bool {
+        let drivers = self.drivers();
+        let count = self.cells.len();
+        let mut removed = HashSet::new();
+        let mut changed = false;
+
+        for index in 0..count {
+            let cell = &self.cells[index];
+
+            // the output of a cell is never replaced if other cells drive it
+            // too
+            let Some(out) = cell.pin("out") else {
+                continue;
+            };
+
+            if drivers[out] != Some(index) {
+                continue;
+            }
+
+            let Some(folded) = self.fold(&drivers, cell, out) else {
+                continue;
+            };
+
+            orphaned.extend(cell.inputs().map(|pin| pin.net));
+            changed = true;
+
+            let replacement = match folded {
+                Folded::Constant(value) => Cell {
+                    kind: CellKind::Const(value),
+                    pins: vec![pin("out", Direction::Out, out)],
+                },
+                Folded::Net(net) => {
+                    self.cells[index].pins.clear();
+                    self.replace_net(out, net);
+                    removed.insert(index);
+                    continue;
+                }
+                Folded::Not(net) => Cell {
+                    kind: CellKind::Not,
+                    pins: vec![
+                        pin("in", Direction::In, net),
+                        pin("out", Direction::Out, out),
+                    ],
+                },
+                Folded::Slice(net, from, to) => Cell {
+                    kind: CellKind::Slice(from, to),
+                    pins: vec![
+                        pin("in", Direction::In, net),
+                        pin("out", Direction::Out, out),
+                    ],
+                },
+                Folded::Concat(parts) => {
+                    let mut pins: Vec<_> = parts
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, part)| {
+                            let net = match part {
+                                Part::Net(net) => net,
+                                Part::Constant(value, width) => self.constant(value, width),
+                            };
+
+                            pin(&index.to_string(), Direction::In, net)
+                        })
+                        .collect();
+                    pins.push(pin("out", Direction::Out, out));
+
+                    Cell {
+                        kind: CellKind::Concat,
+                        pins,
+                    }
+                }
+            };
+
+            self.cells[index] = replacement;
+        }
+
+        let mut index = 0;
+        self.cells.retain(|_| {
+            index += 1;
+            !removed.contains(&(index - 1))
+        });
+
+        changed
+    }
+
+    fn constant_value(&self, drivers: &[Option<usize>], net: NetId) -> Option<u64> {
+        let driver = drivers.get(net).copied().flatten()?;
+
+        match self.cells[driver].kind {
+            CellKind::Const(value) => Some(value & mask(self.width(net))),
+            _ => None,
+        }
+    }
+
+    fn driving_cell<'a>(&'a self, drivers: &[Option<usize>], net: NetId) -> Option<&'a Cell> {
+        drivers
+            .get(net)
+            .copied()
+            .flatten()
+            .map(|driver| &self.cells[driver])
+    }
+
+    fn fold(&self, drivers: &[Option<usize>], cell: &Cell, out: NetId) -> Option<Folded> {
+        let constant = |net| self.constant_value(drivers, net);
+        let all = mask(self.width(out));
+
+        match &cell.kind {
+            CellKind::Gate(op) => {
+                let (a, b) = (cell.pin("a")?, cell.pin("b")?);
+
+                match (constant(a), constant(b)) {
+                    (Some(a), Some(b)) => Some(Folded::Constant(gate(*op, a, b) & all)),
+                    (Some(a), None) => bitwise(gate(*op, a, 0), gate(*op, a, all), b, all),
+                    (None, Some(b)) => bitwise(gate(*op, 0, b), gate(*op, all, b), a, all),
+                    (None, None) if a == b => bitwise(gate(*op, 0, 0), gate(*op, all, all), a, all),
+                    (None, None) => None,
+                }
+            }
+            CellKind::Not => {
+                let input = cell.pin("in")?;
+
+                if let Some(value) = constant(input) {
+                    return Some(Folded::Constant(!value & all));
+                }
+
+                let inner = self.driving_cell(drivers, input)?;
+                match inner.kind {
+                    CellKind::Not => Some(Folded::Net(inner.pin("in")?)),
+                    _ => None,
+                }
+            }
+            CellKind::Slice(from, to) => {
+                let (from, to) = (*from, *to);
+                let input = cell.pin("in")?;
+
+                if let Some(value) = constant(input) {
+                    return Some(Folded::Constant((value >> from) & all));
+                }
+
+                if from == 0 && to + 1 == self.width(input) {
+                    return Some(Folded::Net(input));
+                }
+
+                let inner = self.driving_cell(drivers, input)?;
+                match inner.kind {
+                    CellKind::Slice(offset, _) => {
+                        Some(Folded::Slice(inner.pin("in")?, offset + from, offset + to))
+                    }
+                    // the slice only reads a single part of a concatenation
+                    CellKind::Concat => {
+                        let mut offset = 0;
+
+                        for part in inner.inputs() {
+                            let width = self.width(part.net);
+
+                            if from >= offset && to < offset + width {
+                                return if from == offset && to + 1 == offset + width {
+                                    Some(Folded::Net(part.net))
+                                } else {
+                                    Some(Folded::Slice(part.net, from - offset, to - offset))
+                                };
+                            }
+
+                            offset += width;
+                        }
+
+                        None
+                    }
+                    _ => None,
+                }
+            }
+            CellKind::Concat => {
+                let inputs: Vec<_> = cell.inputs().map(|pin| pin.net).collect();
+
+                // nested concatenations are flattened, neighbouring constants
+                // become a single one
+                let mut parts: Vec<Part> = vec![];
+                let mut changed = false;
+
+                for input in inputs.iter() {
+                    let nested: Vec<_> = match self.driving_cell(drivers, *input) {
+                        Some(inner) if matches!(inner.kind, CellKind::Concat) => {
+                            changed = true;
+                            inner.inputs().map(|pin| pin.net).collect()
+                        }
+                        _ => vec![*input],
+                    };
+
+                    for net in nested {
+                        let width = self.width(net);
+
+                        match (parts.last_mut(), constant(net)) {
+                            (Some(Part::Constant(last, last_width)), Some(value))
+                                if *last_width + width <= 64 =>
+                            {
+                                *last |= value << *last_width;
+                                *last_width += width;
+                                changed = true;
+                            }
+                            (_, Some(value)) => parts.push(Part::Constant(value, width)),
+                            (_, None) => parts.push(Part::Net(net)),
+                        }
+                    }
+                }
+
+                match parts.as_slice() {
+                    [Part::Constant(value, _)] => Some(Folded::Constant(*value)),
+                    [Part::Net(net)] => Some(Folded::Net(*net)),
+                    _ if changed => Some(Folded::Concat(parts)),
+                    _ => None,
+                }
+            }
+            CellKind::Multiplexer => {
+                let selector = cell.pin("sel")?;
+                let values: Vec<_> = cell
+                    .inputs()
+                    .filter(|pin| pin.name != "sel")
+                    .map(|pin| pin.net)
+                    .collect();
+
+                if let Some(selected) = constant(selector) {
+                    return values
+                        .get(selected as usize)
+                        .map(|value| Folded::Net(*value));
+                }
+
+                if values.iter().all(|value| *value == values[0]) {
+                    return Some(Folded::Net(values[0]));
+                }
+
+                let first = constant(values[0])?;
+                values
+                    .iter()
+                    .all(|value| constant(*value) == Some(first))
+                    .then_some(Folded::Constant(first))
+            }
+            _ => None,
+        }
+    }
+
+    // removes the cells whose output was only read by cells that were folded
+    // away, and then the cells only they read
+    fn remove_orphans(&mut self, mut orphaned: HashSet<NetId>) {
+        loop {
+            let readers = self.readers();
+            let count = self.cells.len();
+
+            self.cells.retain(|cell| {
+                let unread = is_pure(cell)
+                    && cell
+                        .outputs()
+                        .all(|pin| orphaned.contains(&pin.net) && readers[pin.net] == 0);
+
+                if unread {
+                    orphaned.extend(cell.inputs().map(|pin| pin.net));
+                }
+
+                !unread
+            });
+
+            if self.cells.len() == count {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use crate::types::module::{ExternalModule, Module};
+
+use super::{NetlistBuilder, NetlistModule, ToNetlist, Value};
+
+impl ToNetlist for Module {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        builder.modules.push(NetlistModule::Internal(self.clone()));
+
+        Value::Empty
+    }
+}
+
+impl ToNetlist for ExternalModule {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        builder
+            .modules
+            .push(NetlistModule::External(Arc::new(self.clone())));
+
+        Value::Empty
+    }
+}
@@ -0,0 +1,39 @@
+use crate::types::program::{Program, ProgramStatement};
+
+use super::{NetlistBuilder, ToNetlist, Value};
+
+impl ToNetlist for Program {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        for statement in &self.statements {
+            statement.to_netlist(builder);
+        }
+
+        Value::Empty
+    }
+}
+
+impl ToNetlist for ProgramStatement {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        match self {
+            ProgramStatement::VariableDefinitions(definitions) => {
+                definitions.to_netlist(builder);
+            }
+            ProgramStatement::Expression(expr) => {
+                expr.to_netlist(builder);
+            }
+            ProgramStatement::Module(module) => {
+                module.to_netlist(builder);
+            }
+            ProgramStatement::ExternalModule(module) => {
+                module.to_netlist(builder);
+            }
+            // bundles are types, there is nothing to convert
+            ProgramStatement::Bundle(_) => {}
+            ProgramStatement::Destructuring(_) => {
+                unreachable!("Destructuring is replaced by the resolver")
+            }
+        }
+
+        Value::Empty
+    }
+}
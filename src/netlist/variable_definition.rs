@@ -0,0 +1,298 @@
+use std::sync::Arc;
+
+use crate::{
+    parser::datatype::KnownBitWidth,
+    types::{
+        decorator::{Decorator, PortType},
+        expression::Expression,
+        variable_definition::VariableDefinitions,
+    },
+};
+
+use super::{pin, CellKind, Direction, NetId, NetlistBuilder, NetlistVariable, ToNetlist, Value};
+
+impl NetlistBuilder {
+    // a net for every field or element of a declared wire, they are connected
+    // once the wire is defined
+    fn declare_wires(&mut self, width: &KnownBitWidth) -> Value {
+        match width {
+            KnownBitWidth::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(field, width)| (field.clone(), Arc::new(self.declare_wires(width))))
+                    .collect(),
+            ),
+            KnownBitWidth::Array(elements) => Value::Array(
+                elements
+                    .iter()
+                    .map(|width| Arc::new(self.declare_wires(width)))
+                    .collect(),
+            ),
+            width => Value::Net(self.netlist.add_net(width.get_size())),
+        }
+    }
+
+    // connects a value to a wire that was declared before, field by field
+    fn connect_declared(&mut self, value: Value, target: &Value) {
+        match (value, target) {
+            (Value::Object(fields), Value::Object(targets)) => {
+                for (key, target) in targets {
+                    let field = fields
+                        .get(key)
+                        .unwrap_or_else(|| panic!("Object does not have key {}", key))
+                        .as_ref()
+                        .clone();
+
+                    self.connect_declared(field, target);
+                }
+            }
+            (Value::Array(elements), Value::Array(targets)) => {
+                for (element, target) in elements.iter().zip(targets) {
+                    self.connect_declared(element.as_ref().clone(), target);
+                }
+            }
+            (value, target) => {
+                let target = target.net();
+                let casted_value =
+                    self.cast_value(value, &KnownBitWidth::Fixed(self.width(target)));
+
+                self.assign(casted_value, target);
+            }
+        }
+    }
+
+    fn add_input(&mut self, label: String, width: u32, default: Option<u32>) -> NetId {
+        let net = self.netlist.add_net(width);
+
+        self.netlist.add_cell(
+            CellKind::Input { label, default },
+            vec![pin("out", Direction::Out, net)],
+        );
+
+        net
+    }
+
+    fn add_output(&mut self, label: String, net: NetId) {
+        self.netlist.add_cell(
+            CellKind::Output { label },
+            vec![pin("in", Direction::In, net)],
+        );
+    }
+
+    // an input for every field of a bundle, labelled `label_field`, or every
+    // element of an array, labelled `label_index`
+    fn add_bundle_inputs(&mut self, label: String, width: &KnownBitWidth) -> Value {
+        match width {
+            KnownBitWidth::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(field, width)| {
+                        let value = self.add_bundle_inputs(format!("{}_{}", label, field), width);
+
+                        (field.clone(), Arc::new(value))
+                    })
+                    .collect(),
+            ),
+            KnownBitWidth::Array(elements) => Value::Array(
+                elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, width)| {
+                        let value = self.add_bundle_inputs(format!("{}_{}", label, index), width);
+
+                        Arc::new(value)
+                    })
+                    .collect(),
+            ),
+            width => Value::Net(self.add_input(label, width.get_size(), None)),
+        }
+    }
+
+    // an output for every field of a bundle, labelled `label_field`, or every
+    // element of an array, labelled `label_index`
+    fn add_bundle_outputs(&mut self, label: String, value: &Value) {
+        match value {
+            Value::Object(fields) => {
+                for (field, value) in fields {
+                    self.add_bundle_outputs(format!("{}_{}", label, field), value);
+                }
+            }
+            Value::Array(elements) => {
+                for (index, value) in elements.iter().enumerate() {
+                    self.add_bundle_outputs(format!("{}_{}", label, index), value);
+                }
+            }
+            value => self.add_output(label, value.net()),
+        }
+    }
+}
+
+impl ToNetlist for VariableDefinitions {
+    fn to_netlist(&self, builder: &mut NetlistBuilder) -> Value {
+        match &self.decorator {
+            None => {
+                for def in self.definitions.iter() {
+                    let Some(expression) = &def.value else {
+                        panic!("Variable {} has no value", def.name);
+                    };
+
+                    let mut value = expression.to_netlist(builder);
+
+                    if let Some(port_type) = &def.port_type {
+                        value = builder.cast_object(value, &port_type.width());
+                    }
+
+                    let potential_variable = builder.find_variable(def.name.clone());
+
+                    if let Some(potential_variable) = potential_variable {
+                        if potential_variable.undefined {
+                            builder.connect_declared(value, &potential_variable.value);
+                        } else {
+                            panic!("Variable {} already defined", def.name);
+                        }
+                    } else {
+                        builder.add_variable(NetlistVariable {
+                            name: def.name.clone(),
+                            value,
+                            undefined: false,
+                        });
+                    }
+                }
+            }
+            // inside a module the inputs are added when the module is used,
+            // see ModuleUse
+            Some(Decorator::In(..) | Decorator::InOut(..)) if !builder.is_top() => {}
+            Some(Decorator::In(port_type @ (PortType::Bundle(..) | PortType::Array(..)), name)) => {
+                let width = port_type.width();
+
+                for def in self.definitions.iter() {
+                    let label = name.clone().unwrap_or_else(|| def.name.clone());
+                    let value = builder.add_bundle_inputs(label, &width);
+
+                    builder.add_variable(NetlistVariable {
+                        name: def.name.clone(),
+                        value,
+                        undefined: false,
+                    });
+                }
+            }
+            Some(Decorator::In(PortType::Bits(bits), name)) => {
+                for def in self.definitions.iter() {
+                    let label = name.clone().unwrap_or_else(|| def.name.clone());
+
+                    // the resolver only allows constant defaults
+                    let default = match def.value.as_ref().map(|value| &value.expression) {
+                        Some(Expression::Integer(default)) => Some(*default),
+                        _ => None,
+                    };
+
+                    let net = builder.add_input(label, *bits, default);
+
+                    builder.add_variable(NetlistVariable {
+                        name: def.name.clone(),
+                        value: Value::Net(net),
+                        undefined: false,
+                    });
+                }
+            }
+            Some(Decorator::InOut(bits, name)) => {
+                for def in self.definitions.iter() {
+                    let label = name.clone().unwrap_or_else(|| def.name.clone());
+                    let net = builder.netlist.add_net(*bits);
+
+                    builder.netlist.add_cell(
+                        CellKind::Input {
+                            label,
+                            default: None,
+                        },
+                        vec![pin("out", Direction::InOut, net)],
+                    );
+
+                    builder.add_variable(NetlistVariable {
+                        name: def.name.clone(),
+                        value: Value::Net(net),
+                        undefined: true,
+                    });
+                }
+            }
+            Some(Decorator::Clock(frequency)) => {
+                for def in self.definitions.iter() {
+                    let net = builder.netlist.add_net(1);
+
+                    builder.netlist.add_cell(
+                        CellKind::Clock {
+                            label: def.name.clone(),
+                            frequency: *frequency,
+                        },
+                        vec![pin("out", Direction::Out, net)],
+                    );
+
+                    builder.add_variable(NetlistVariable {
+                        name: def.name.clone(),
+                        value: Value::Net(net),
+                        undefined: false,
+                    });
+                }
+            }
+            Some(Decorator::Out(port_type, name)) => {
+                for def in self.definitions.iter() {
+                    let expression = def
+                        .value
+                        .as_ref()
+                        .unwrap_or_else(|| panic!("Output variable {} has no value", def.name));
+
+                    let value = expression.to_netlist(builder);
+
+                    // bundles and arrays become one output per field or
+                    // element
+                    let value = match (port_type, value) {
+                        (Some(port_type @ (PortType::Bundle(..) | PortType::Array(..))), value) => {
+                            builder.cast_object(value, &port_type.width())
+                        }
+                        (_, value @ Value::Array(_)) => value,
+                        (Some(PortType::Bits(bits)), value) => {
+                            Value::Net(builder.cast_value(value, &KnownBitWidth::Fixed(*bits)))
+                        }
+                        (None, value) => Value::Net(value.net()),
+                    };
+
+                    if builder.is_top() {
+                        let label = name.clone().unwrap_or_else(|| def.name.clone());
+                        builder.add_bundle_outputs(label, &value);
+                    }
+
+                    builder.add_variable(NetlistVariable {
+                        name: def.name.clone(),
+                        value,
+                        undefined: false,
+                    });
+                }
+            }
+            Some(Decorator::Global) => {
+                for def in self.definitions.iter() {
+                    let variable = builder.find_global(def.name.clone()).unwrap_or_else(|| {
+                        panic!("Global variable {} not found", def.name);
+                    });
+
+                    builder.add_variable(variable);
+                }
+            }
+            Some(Decorator::Wire(port_type)) => {
+                // the resolver infers every missing width
+                let width = port_type.as_ref().unwrap().width();
+
+                for def in self.definitions.iter() {
+                    let value = builder.declare_wires(&width);
+
+                    builder.add_variable(NetlistVariable {
+                        name: def.name.clone(),
+                        value,
+                        undefined: true,
+                    });
+                }
+            }
+        }
+
+        Value::Empty
+    }
+}
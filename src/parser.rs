@@ -1,15 +1,27 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-use datatype::{GetBitWidth, KnownBitWidth};
-use winnow::{PResult, Stateful};
+use datatype::KnownBitWidth;
+use winnow::Stateful;
 
-use crate::types::program::Program;
+use crate::{
+    preprocessor::{Preprocessed, SourceLocation},
+    types::program::Program,
+};
 
 mod argument;
+mod bundle;
+mod constant;
 pub mod datatype;
 mod decorator;
 mod expression;
+mod generate;
 mod identifier;
+mod import;
 mod module;
 mod number;
 mod program;
@@ -20,152 +32,140 @@ mod whitespace;
 #[derive(Debug, Clone)]
 pub struct ParserModuleVariableData {
     pub name: String,
-    pub external_name: String,
     pub width: KnownBitWidth,
+    // the value of an input that isn't connected, `@in(1) carry = 0`
+    pub default: Option<u32>,
 }
 
-#[derive(Debug, Clone)]
-pub enum ParserModuleVariable {
-    Input(ParserModuleVariableData),
-    Output(ParserModuleVariableData),
-    Clock(ParserModuleVariableData),
-    UndefinedWire(ParserModuleVariableData),
-    DefinedWire(ParserModuleVariableData),
-}
-
-impl GetBitWidth for ParserModuleVariable {
-    fn get_bit_width(&self, _state: &ParserState) -> datatype::KnownBitWidth {
-        match self {
-            ParserModuleVariable::Input(data) => data.width.clone(),
-            ParserModuleVariable::Clock(data) => data.width.clone(),
-            ParserModuleVariable::Output(data) => data.width.clone(),
-            ParserModuleVariable::DefinedWire(data) => data.width.clone(),
-            ParserModuleVariable::UndefinedWire(data) => data.width.clone(),
-        }
-    }
+#[derive(Debug, Clone, Default)]
+pub struct ParserOptions {
+    // macros defined before preprocessing each file
+    pub defines: Vec<(String, String)>,
+    // directories searched for imports that aren't next to the importing file
+    pub search_path: Vec<PathBuf>,
 }
 
+// the constants are the only names the parser knows, everything else is
+// bookkeeping for imports and error messages
 #[derive(Debug, Clone)]
-pub struct ParserModule {
-    name: String,
-    variables: Vec<ParserModuleVariable>,
-}
-
-#[derive(Debug)]
-pub struct ParserModuleInOut {
-    pub inputs: Vec<ParserModuleVariableData>,
-    pub outputs: Vec<ParserModuleVariableData>,
-}
-
-#[derive(Debug)]
 pub struct ParserState {
-    modules_stack: Vec<ParserModule>,
-    all_modules: HashMap<String, ParserModuleInOut>,
+    // where the constants of every module that is being parsed start
+    modules_stack: Vec<usize>,
+    constants: Vec<(String, u32)>,
+
+    options: ParserOptions,
+    // files currently being parsed, the innermost one last
+    file_stack: Vec<PathBuf>,
+    sources: Vec<Rc<Preprocessed>>,
+    // every file that has been loaded, with the modules and bundles it defines
+    loaded_files: HashMap<PathBuf, Vec<String>>,
+    // set when an error has to be reported somewhere other than where
+    // parsing stopped, e.g. inside an imported file
+    error: Option<ParseError>,
 }
 
 impl ParserState {
-    pub fn new() -> Self {
+    pub fn new(options: ParserOptions) -> Self {
         Self {
-            modules_stack: vec![ParserModule::new(String::from("$"))],
-            all_modules: HashMap::new(),
+            modules_stack: vec![],
+            constants: vec![],
+
+            options,
+            file_stack: vec![],
+            sources: vec![],
+            loaded_files: HashMap::new(),
+            error: None,
         }
     }
 
-    pub fn start_new_module(&mut self, name: String) {
-        self.modules_stack.push(ParserModule::new(name));
+    // the original location of the input that has `remaining` bytes left in
+    // the file that is currently being parsed
+    pub fn location(&self, remaining: usize) -> Option<SourceLocation> {
+        let source = self.sources.last()?;
+        let offset = source.source.len().saturating_sub(remaining);
+
+        source
+            .source_map
+            .locate(&source.source, offset)
+            .cloned()
+    }
+
+    pub fn is_top(&self) -> bool {
+        self.modules_stack.is_empty()
     }
 
-    pub fn current_module(&mut self) -> &mut ParserModule {
-        self.modules_stack.last_mut().unwrap()
+    pub fn push_constant(&mut self, name: String, value: u32) {
+        self.constants.push((name, value));
     }
 
-    pub fn add_variable(&mut self, variable: ParserModuleVariable) {
-        self.current_module().add_variable(variable);
+    pub fn constants_len(&self) -> usize {
+        self.constants.len()
     }
 
-    pub fn find_variable(&self, name: &str) -> Option<&ParserModuleVariable> {
-        for module in self.modules_stack.iter().rev() {
-            for variable in &module.variables {
-                match variable {
-                    ParserModuleVariable::Input(data) => {
-                        if data.name == name {
-                            return Some(variable);
-                        }
-                    }
-                    ParserModuleVariable::Output(data) => {
-                        if data.name == name {
-                            return Some(variable);
-                        }
-                    }
-                    ParserModuleVariable::DefinedWire(data) => {
-                        if data.name == name {
-                            return Some(variable);
-                        }
-                    }
-                    ParserModuleVariable::UndefinedWire(data) => {
-                        if data.name == name {
-                            return Some(variable);
-                        }
-                    }
-                    ParserModuleVariable::Clock(data) => {
-                        if data.name == name {
-                            return Some(variable);
-                        }
-                    }
-                }
-            }
-        }
+    // drops every constant defined after the scope that started at `len`
+    pub fn truncate_constants(&mut self, len: usize) {
+        self.constants.truncate(len);
+    }
 
-        None
+    pub fn find_constant(&self, name: &str) -> Option<u32> {
+        self.constants
+            .iter()
+            .rev()
+            .find(|(constant, _)| constant == name)
+            .map(|(_, value)| *value)
     }
 
-    pub fn find_module(&self, name: &str) -> Option<&ParserModuleInOut> {
-        self.all_modules.get(name)
+    pub fn start_new_module(&mut self) {
+        self.modules_stack.push(self.constants.len());
     }
 
-    pub fn end_current_module(&mut self) -> ParserModule {
-        let module = self.modules_stack.pop().unwrap();
+    pub fn end_current_module(&mut self) {
+        let constants_start = self.modules_stack.pop().unwrap();
 
-        let mut inputs = vec![];
-        let mut outputs = vec![];
+        // constants defined inside the module are not visible outside of it
+        self.constants.truncate(constants_start);
+    }
+}
 
-        for variable in module.variables.iter() {
-            match variable {
-                ParserModuleVariable::Input(data) => inputs.push(data.clone()),
-                ParserModuleVariable::Clock(data) => inputs.push(data.clone()),
-                ParserModuleVariable::Output(data) => outputs.push(data.clone()),
-                ParserModuleVariable::DefinedWire(_) => {}
-                ParserModuleVariable::UndefinedWire(_) => {}
-            }
-        }
+pub type Stream<'is> = Stateful<&'is str, ParserState>;
 
-        self.all_modules
-            .insert(module.name.clone(), ParserModuleInOut { inputs, outputs });
+// an error in otherwise valid syntax, like an import that doesn't exist. it
+// is reported where parsing is right now, instead of a syntax error where
+// parsing stopped.
+pub fn located_error(
+    input: &mut Stream,
+    message: String,
+) -> winnow::error::ErrMode<winnow::error::ContextError> {
+    input.state.error = Some(ParseError {
+        location: input.state.location(input.input.len()),
+        message,
+    });
+
+    // there is no point in trying anything else
+    winnow::error::ErrMode::Cut(winnow::error::ContextError::new())
+}
 
-        module
-    }
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub location: Option<SourceLocation>,
+    pub message: String,
 }
 
-impl ParserModule {
-    pub fn new(name: String) -> Self {
-        Self {
-            variables: Vec::new(),
-            name,
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: {}", location, self.message),
+            None => write!(f, "{}", self.message),
         }
     }
-
-    pub fn add_variable(&mut self, variable: ParserModuleVariable) {
-        self.variables.push(variable);
-    }
 }
 
-pub type Stream<'is> = Stateful<&'is str, ParserState>;
-
-pub fn parse_program(input: &str) -> PResult<Program> {
-    let mut stream = Stream {
-        input,
-        state: ParserState::new(),
-    };
+// preprocesses and parses a file, together with everything it imports.
+// constants are evaluated and generate blocks unrolled on the way, nothing
+// else is looked up: names and widths are left to the resolver, every width
+// in the program is still unresolved.
+pub fn parse_file(path: &Path, options: ParserOptions) -> Result<Program, ParseError> {
+    let mut state = ParserState::new(options);
 
-    program::parse_program(&mut stream)
+    import::parse_file(path, &mut state)
 }
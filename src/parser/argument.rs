@@ -5,7 +5,6 @@ use winnow::{combinator, PResult, Parser};
 use crate::types::{argument::Argument, expression::ExpressionWithWidth};
 
 use super::{
-    datatype::GetBitWidth,
     expression::parse_expression,
     identifier::parse_identifier,
     trivial_tokens::{parse_close_paren, parse_colon, parse_comma, parse_open_paren},
@@ -25,16 +24,19 @@ pub fn parse_argument(input: &mut Stream) -> PResult<Argument> {
 
     Ok(Argument {
         name,
-        value: ExpressionWithWidth {
-            width: value.get_bit_width(&input.state),
-            expression: value,
-        },
+        value: ExpressionWithWidth::new(value),
     })
 }
 
+// the arguments in the order they are given, positional and named ones mixed
+pub fn parse_argument_list(input: &mut Stream) -> PResult<Vec<Argument>> {
+    parse_whitespace(input)?;
+    combinator::separated(0.., parse_argument, parse_comma).parse_next(input)
+}
+
 pub fn parse_arguments_inner(input: &mut Stream) -> PResult<HashMap<String, Argument>> {
     parse_whitespace(input)?;
-    let arguments = combinator::separated(0.., parse_argument, parse_comma).parse_next(input)?;
+    let arguments = parse_argument_list(input)?;
     Ok(create_argument_map(arguments))
 }
 
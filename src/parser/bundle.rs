@@ -0,0 +1,79 @@
+use winnow::{combinator, PResult, Parser};
+
+use crate::types::{
+    bundle::{Bundle, BundleField},
+    decorator::PortType,
+};
+
+use super::{
+    constant::parse_const_expression,
+    datatype::KnownBitWidth,
+    identifier::{parse_identifier, parse_keyword},
+    trivial_tokens::{
+        parse_close_scope, parse_close_square_bracket, parse_colon, parse_comma, parse_open_scope,
+        parse_open_square_bracket, parse_semicolon,
+    },
+    whitespace::parse_whitespace,
+    Stream,
+};
+
+// `[8; 16]`, an array of any other field type
+fn parse_array_type(input: &mut Stream) -> PResult<PortType> {
+    parse_whitespace(input)?;
+
+    combinator::delimited(
+        parse_open_square_bracket,
+        (
+            parse_bundle_field_type,
+            parse_semicolon,
+            parse_const_expression,
+        ),
+        parse_close_square_bracket,
+    )
+    .map(|(element, _, length)| PortType::Array(Box::new(element), length))
+    .parse_next(input)
+}
+
+// `8`, `WIDTH`, `MemBus` or `[8; 16]`
+pub fn parse_bundle_field_type(input: &mut Stream) -> PResult<PortType> {
+    parse_whitespace(input)?;
+
+    combinator::alt((
+        parse_array_type,
+        parse_const_expression.map(PortType::Bits),
+        parse_identifier.map(|s| PortType::Bundle(s.to_string(), KnownBitWidth::Unresolved)),
+    ))
+    .parse_next(input)
+}
+
+fn parse_bundle_field(input: &mut Stream) -> PResult<BundleField> {
+    parse_whitespace(input)?;
+
+    combinator::seq!(BundleField {
+        name: parse_identifier.map(|s| s.to_string()),
+        _: parse_colon,
+        port_type: parse_bundle_field_type,
+    })
+    .parse_next(input)
+}
+
+// `bundle MemBus { addr: 16, data: 8, we: 1 }`, a field can be another bundle
+pub fn parse_bundle(input: &mut Stream) -> PResult<Bundle> {
+    parse_whitespace(input)?;
+
+    parse_keyword(input, "bundle")?;
+
+    let name = parse_identifier.map(|s| s.to_string()).parse_next(input)?;
+
+    let fields = combinator::delimited(
+        parse_open_scope,
+        combinator::terminated(
+            combinator::separated(1.., parse_bundle_field, parse_comma),
+            combinator::opt(parse_comma), // optional trailing comma
+        ),
+        parse_close_scope,
+    )
+    .parse_next(input)?;
+
+    Ok(Bundle { name, fields })
+}
@@ -0,0 +1,230 @@
+use winnow::{combinator, PResult, Parser};
+
+use crate::utils::integer_width::ceil_log2;
+
+use super::{
+    identifier::{parse_identifier, parse_keyword},
+    located_error,
+    number::parse_number_u32,
+    trivial_tokens::{
+        parse_bang_equals, parse_close_paren, parse_double_amperstand, parse_double_equals,
+        parse_double_pipe, parse_equals, parse_greater, parse_greater_equals, parse_less,
+        parse_less_equals, parse_minus, parse_open_paren, parse_percent, parse_plus, parse_slash,
+        parse_star,
+    },
+    whitespace::parse_whitespace,
+    Stream,
+};
+
+// constant expressions are evaluated while parsing, so the only names they
+// can refer to are the ones currently bound in the parser state (constants
+// and loop variables)
+fn parse_constant_variable(input: &mut Stream) -> PResult<u32> {
+    parse_whitespace(input)?;
+
+    let name = parse_identifier(input)?;
+
+    input
+        .state
+        .find_constant(name)
+        .ok_or_else(|| winnow::error::ErrMode::Backtrack(winnow::error::ContextError::new()))
+}
+
+fn parse_const_function(input: &mut Stream) -> PResult<u32> {
+    parse_whitespace(input)?;
+
+    let (name, argument) = (
+        parse_identifier,
+        combinator::delimited(parse_open_paren, parse_const_expression, parse_close_paren),
+    )
+        .parse_next(input)?;
+
+    match name {
+        "clog2" => Ok(ceil_log2(argument)),
+        _ => Err(winnow::error::ErrMode::Backtrack(
+            winnow::error::ContextError::new(),
+        )),
+    }
+}
+
+// constants are unsigned 32 bit numbers, anything else is a mistake in the
+// program
+fn arithmetic_error(
+    input: &mut Stream,
+    lhs: u32,
+    op: &str,
+    rhs: u32,
+    problem: &str,
+) -> winnow::error::ErrMode<winnow::error::ContextError> {
+    located_error(
+        input,
+        format!("The constant expression {} {} {} {}", lhs, op, rhs, problem),
+    )
+}
+
+// `remainder` is false where `%` is the multiplexer instead
+fn parse_const_product(input: &mut Stream, remainder: bool) -> PResult<u32> {
+    parse_whitespace(input)?;
+
+    let mut value = parse_const_operand(input, remainder)?;
+
+    // the operator and its operand are parsed together so that a trailing
+    // `*` (e.g. the start of an external module) is left alone
+    while let Some((op, rhs)) = combinator::opt((
+        combinator::alt((
+            parse_star,
+            parse_slash,
+            parse_percent.verify(|_: &str| remainder),
+        )),
+        |input: &mut Stream| parse_const_operand(input, remainder),
+    ))
+    .parse_next(input)?
+    {
+        let result = match op {
+            "*" => value.checked_mul(rhs).ok_or("overflows"),
+            "/" => value.checked_div(rhs).ok_or("divides by zero"),
+            "%" => value.checked_rem(rhs).ok_or("divides by zero"),
+            _ => unreachable!(),
+        };
+
+        value = result.map_err(|problem| arithmetic_error(input, value, op, rhs, problem))?;
+    }
+
+    Ok(value)
+}
+
+fn parse_const_sum(input: &mut Stream, remainder: bool) -> PResult<u32> {
+    parse_whitespace(input)?;
+
+    let mut value = parse_const_product(input, remainder)?;
+
+    while let Some((op, rhs)) = combinator::opt((
+        combinator::alt((parse_plus, parse_minus)),
+        |input: &mut Stream| parse_const_product(input, remainder),
+    ))
+    .parse_next(input)?
+    {
+        let result = match op {
+            "+" => value.checked_add(rhs).ok_or("overflows"),
+            "-" => value.checked_sub(rhs).ok_or("is negative"),
+            _ => unreachable!(),
+        };
+
+        value = result.map_err(|problem| arithmetic_error(input, value, op, rhs, problem))?;
+    }
+
+    Ok(value)
+}
+
+pub fn parse_const_expression(input: &mut Stream) -> PResult<u32> {
+    parse_const_sum(input, true)
+}
+
+// a plain value in a signal expression, where `a % b` always selects, so
+// `WIDTH - 1` is folded but `7 % 4` stays a multiplexer
+pub fn parse_const_value(input: &mut Stream) -> PResult<u32> {
+    parse_const_sum(input, false)
+}
+
+fn parse_const_comparison(input: &mut Stream) -> PResult<bool> {
+    parse_whitespace(input)?;
+
+    let lhs = parse_const_expression(input)?;
+
+    let comparison = combinator::opt((
+        combinator::alt((
+            parse_double_equals,
+            parse_bang_equals,
+            parse_less_equals,
+            parse_greater_equals,
+            parse_less,
+            parse_greater,
+        )),
+        parse_const_expression,
+    ))
+    .parse_next(input)?;
+
+    Ok(match comparison {
+        Some(("==", rhs)) => lhs == rhs,
+        Some(("!=", rhs)) => lhs != rhs,
+        Some(("<=", rhs)) => lhs <= rhs,
+        Some((">=", rhs)) => lhs >= rhs,
+        Some(("<", rhs)) => lhs < rhs,
+        Some((">", rhs)) => lhs > rhs,
+        Some(_) => unreachable!(),
+        // a bare value is true when it is not zero
+        None => lhs != 0,
+    })
+}
+
+fn parse_const_conjunction(input: &mut Stream) -> PResult<bool> {
+    parse_whitespace(input)?;
+
+    let mut value = parse_const_comparison(input)?;
+
+    while let Some((_, rhs)) =
+        combinator::opt((parse_double_amperstand, parse_const_comparison)).parse_next(input)?
+    {
+        value = value && rhs;
+    }
+
+    Ok(value)
+}
+
+// conditions of `if` statements, e.g. `WIDTH > 8 && DEPTH != 0`
+pub fn parse_const_condition(input: &mut Stream) -> PResult<bool> {
+    parse_whitespace(input)?;
+
+    let mut value = parse_const_conjunction(input)?;
+
+    while let Some((_, rhs)) =
+        combinator::opt((parse_double_pipe, parse_const_conjunction)).parse_next(input)?
+    {
+        value = value || rhs;
+    }
+
+    Ok(value)
+}
+
+// an index is either a plain number, a constant name, a function call or a
+// parenthesized constant expression, e.g. `a.3`, `a.i`, `a.(i + 1)`
+pub fn parse_index(input: &mut Stream) -> PResult<u32> {
+    parse_const_operand(input, true)
+}
+
+fn parse_const_operand(input: &mut Stream, remainder: bool) -> PResult<u32> {
+    parse_whitespace(input)?;
+
+    combinator::alt((
+        parse_number_u32,
+        parse_const_function,
+        parse_constant_variable,
+        combinator::delimited(
+            parse_open_paren,
+            |input: &mut Stream| parse_const_sum(input, remainder),
+            parse_close_paren,
+        ),
+    ))
+    .parse_next(input)
+}
+
+// `const NAME = <constant expression>`
+pub fn parse_const_definition(input: &mut Stream) -> PResult<()> {
+    parse_whitespace(input)?;
+
+    parse_keyword(input, "const")?;
+    let name = parse_identifier.map(|s| s.to_string()).parse_next(input)?;
+    parse_equals(input)?;
+
+    let value = parse_const_expression(input).map_err(|e| match e {
+        winnow::error::ErrMode::Cut(_) => e,
+        _ => located_error(
+            input,
+            format!("Value of constant {} is not a constant expression", name),
+        ),
+    })?;
+
+    input.state.push_constant(name, value);
+
+    Ok(())
+}
@@ -1,13 +1,13 @@
 use std::{collections::HashMap, sync::Arc};
 
-use super::ParserState;
-
-mod expression;
-
 #[derive(Clone, Debug)]
 pub enum KnownBitWidth {
     Fixed(u32),
     Object(HashMap<String, Arc<KnownBitWidth>>),
+    // elements are indexed from 0, usually all of them have the same width
+    Array(Vec<Arc<KnownBitWidth>>),
+    // set by the parser, the resolver fills in the actual width
+    Unresolved,
 }
 
 impl KnownBitWidth {
@@ -21,13 +21,16 @@ impl KnownBitWidth {
 
                 map.values().next().unwrap().get_size()
             }
+            KnownBitWidth::Array(_) => panic!("Array width has no size"),
+            KnownBitWidth::Unresolved => panic!("Bit width has not been resolved"),
         }
     }
+    // objects and arrays are made of several wires and can't be cast
+    pub fn is_structured(&self) -> bool {
+        matches!(self, KnownBitWidth::Object(_) | KnownBitWidth::Array(_))
+    }
+
     pub fn max(left: KnownBitWidth, right: KnownBitWidth) -> KnownBitWidth {
         KnownBitWidth::Fixed(left.get_size().max(right.get_size()))
     }
 }
-
-pub trait GetBitWidth {
-    fn get_bit_width(&self, state: &ParserState) -> KnownBitWidth;
-}
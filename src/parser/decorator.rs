@@ -1,12 +1,41 @@
+use std::collections::HashMap;
+
 use winnow::PResult;
 
-use crate::types::{decorator::Decorator, expression::Expression};
+use crate::types::{
+    argument::Argument,
+    decorator::{Decorator, PortType},
+    expression::{Expression, Repeat},
+};
 
 use super::{
-    argument::parse_arguments, identifier::parse_identifier, trivial_tokens::parse_at,
-    whitespace::parse_whitespace, Stream,
+    argument::parse_arguments, datatype::KnownBitWidth, identifier::parse_identifier,
+    trivial_tokens::parse_at, whitespace::parse_whitespace, Stream,
 };
 
+fn port_type(expression: &Expression) -> Option<PortType> {
+    match expression {
+        Expression::Integer(bits) => Some(PortType::Bits(*bits)),
+        Expression::Variable(bundle) => {
+            Some(PortType::Bundle(bundle.clone(), KnownBitWidth::Unresolved))
+        }
+        Expression::Repeat(Repeat { value, count }) => Some(PortType::Array(
+            Box::new(port_type(&value.expression)?),
+            *count,
+        )),
+        _ => None,
+    }
+}
+
+// `@in(8)`, `@in(WIDTH)`, `@in(MemBus)` or `@in([8; 16])`. bundles are looked
+// up by the resolver, since they can be declared further down.
+fn parse_port_type(arguments: &HashMap<String, Argument>) -> Option<PortType> {
+    arguments
+        .get("bits")
+        .or_else(|| arguments.get("0"))
+        .and_then(|arg| port_type(&arg.value.expression))
+}
+
 pub fn parse_decorator(input: &mut Stream) -> PResult<Decorator> {
     parse_whitespace(input)?;
 
@@ -18,17 +47,6 @@ pub fn parse_decorator(input: &mut Stream) -> PResult<Decorator> {
 
     match decorator {
         "out" => {
-            let bits = arguments
-                .get("bits")
-                .or_else(|| arguments.get("0"))
-                .and_then(|arg| {
-                    if let Expression::Integer(bits) = arg.value.clone().expression {
-                        Some(bits)
-                    } else {
-                        None
-                    }
-                });
-
             let name = arguments.get("name").and_then(|arg| {
                 if let Expression::String(name) = arg.value.clone().expression {
                     Some(name)
@@ -37,9 +55,22 @@ pub fn parse_decorator(input: &mut Stream) -> PResult<Decorator> {
                 }
             });
 
-            Ok(Decorator::Out(bits, name))
+            Ok(Decorator::Out(parse_port_type(&arguments), name))
         }
         "in" => {
+            let port_type = parse_port_type(&arguments).unwrap_or(PortType::Bits(1));
+
+            let name = arguments.get("name").and_then(|arg| {
+                if let Expression::String(name) = arg.value.clone().expression {
+                    Some(name)
+                } else {
+                    None
+                }
+            });
+
+            Ok(Decorator::In(port_type, name))
+        }
+        "inout" => {
             let bits = arguments
                 .get("bits")
                 .or_else(|| arguments.get("0"))
@@ -60,7 +91,7 @@ pub fn parse_decorator(input: &mut Stream) -> PResult<Decorator> {
                 }
             });
 
-            Ok(Decorator::In(bits, name))
+            Ok(Decorator::InOut(bits, name))
         }
         "clock" => {
             let freq = arguments
@@ -77,20 +108,11 @@ pub fn parse_decorator(input: &mut Stream) -> PResult<Decorator> {
             Ok(Decorator::Clock(freq))
         }
         "wire" => {
-            let bits = arguments
-                .get("bits")
-                .or_else(|| arguments.get("0"))
-                .and_then(|arg| {
-                    if let Expression::Integer(bits) = arg.value.clone().expression {
-                        Some(bits)
-                    } else {
-                        None
-                    }
-                })
-                .map_or(1, |x| x);
+            let port_type = parse_port_type(&arguments);
 
-            Ok(Decorator::Wire(bits))
+            Ok(Decorator::Wire(port_type))
         }
+        "global" => Ok(Decorator::Global),
 
         _ => Err(winnow::error::ErrMode::Backtrack(
             winnow::error::ContextError::new(),
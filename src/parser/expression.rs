@@ -6,18 +6,20 @@ use winnow::{
 };
 
 use crate::types::expression::{
-    BinaryOp, Combine, Expression, ExpressionWithWidth, Extract, ExtractInner, ModuleUse, UnaryOp,
+    BinaryOp, Combine, Drive, Expression, ExpressionWithWidth, Extract, ExtractInner, ModuleUse,
+    Repeat, UnaryOp,
 };
 
 use super::{
-    argument::parse_arguments_inner,
-    identifier::{parse_identifier, parse_string},
-    number::parse_number_u32,
+    argument::parse_argument_list,
+    constant::{parse_const_expression, parse_const_value, parse_index},
+    generate::parse_for,
+    identifier::{parse_identifier, parse_indexed_identifier, parse_keyword, parse_string},
     trivial_tokens::{
         parse_amperstand, parse_bang, parse_bang_amperstand, parse_bang_caret, parse_bang_pipe,
-        parse_caret, parse_close_paren, parse_close_square_bracket, parse_colon, parse_comma,
-        parse_dot, parse_double_dot, parse_open_paren, parse_open_square_bracket, parse_percent,
-        parse_pipe,
+        parse_caret, parse_close_paren, parse_close_scope, parse_close_square_bracket,
+        parse_colon, parse_comma, parse_dot, parse_double_dot, parse_open_paren,
+        parse_open_square_bracket, parse_percent, parse_pipe, parse_semicolon,
     },
     whitespace::parse_whitespace,
     Stream,
@@ -41,15 +43,33 @@ pub fn parse_term(input: &mut Stream) -> PResult<Expression> {
     parse_whitespace(input)?;
 
     let mut expression = combinator::alt((
-        parse_variable_expression,
         parse_integer_expression,
+        parse_drive_expression,
+        parse_variable_expression,
         parse_string.map(Expression::String),
+        parse_repeat_expression,
         parse_combine_expression,
         parse_paren_expression,
     ))
     .parse_next(input)?;
 
     loop {
+        // like with names, the bracket must directly follow the expression
+        let index = combinator::opt(combinator::delimited(
+            "[",
+            parse_const_expression,
+            parse_close_square_bracket,
+        ))
+        .parse_next(input)?;
+
+        if let Some(index) = index {
+            expression = Expression::Extract(Extract {
+                expression: Arc::new(ExpressionWithWidth::new(expression)),
+                extract: ExtractInner::Element(index),
+            });
+            continue;
+        }
+
         let postfix = parse_postfix_operator(input);
 
         if let Ok(postfix) = postfix {
@@ -58,13 +78,13 @@ pub fn parse_term(input: &mut Stream) -> PResult<Expression> {
                     let extract = parse_extract(input)?;
 
                     expression = Expression::Extract(Extract {
-                        expression: Arc::new(ExpressionWithWidth::new(expression, &input.state)),
+                        expression: Arc::new(ExpressionWithWidth::new(expression)),
                         extract,
                     });
                 }
                 "(" => {
                     let arguments =
-                        combinator::terminated(parse_arguments_inner, parse_close_paren)
+                        combinator::terminated(parse_argument_list, parse_close_paren)
                             .parse_next(input)?;
 
                     if let Expression::Variable(name) = expression {
@@ -102,14 +122,54 @@ fn parse_paren_expression(input: &mut Stream) -> PResult<Expression> {
 fn parse_integer_expression(input: &mut Stream) -> PResult<Expression> {
     parse_whitespace(input)?;
 
-    parse_number_u32.map(Expression::Integer).parse_next(input)
+    // constant expressions are folded right away, so `WIDTH * 2 - 1` is
+    // just an integer from here on
+    parse_const_value
+        .map(Expression::Integer)
+        .parse_next(input)
+}
+
+// `drive(value, enable)`
+fn parse_drive_expression(input: &mut Stream) -> PResult<Expression> {
+    parse_whitespace(input)?;
+
+    parse_keyword(input, "drive")?;
+
+    let (value, _, enable) = combinator::delimited(
+        parse_open_paren,
+        (parse_expression, parse_comma, parse_expression),
+        parse_close_paren,
+    )
+    .parse_next(input)?;
+
+    Ok(Expression::Drive(Drive {
+        value: Arc::new(ExpressionWithWidth::new(value)),
+        enable: Arc::new(ExpressionWithWidth::new(enable)),
+    }))
+}
+
+// `[value; count]`
+fn parse_repeat_expression(input: &mut Stream) -> PResult<Expression> {
+    parse_whitespace(input)?;
+
+    let (value, _, count) = combinator::delimited(
+        parse_open_square_bracket,
+        (parse_expression, parse_semicolon, parse_const_expression),
+        parse_close_square_bracket,
+    )
+    .parse_next(input)?;
+
+    Ok(Expression::Repeat(Repeat {
+        value: Arc::new(ExpressionWithWidth::new(value)),
+        count,
+    }))
 }
 
 fn parse_variable_expression(input: &mut Stream) -> PResult<Expression> {
     parse_whitespace(input)?;
 
-    parse_identifier
-        .map(|s| Expression::Variable(s.to_string()))
+    parse_indexed_identifier
+        .map(Expression::Variable)
         .parse_next(input)
 }
 
@@ -146,33 +206,33 @@ fn parse_binary_expression(input: &mut Stream) -> PResult<Expression> {
         let rhs = half_binary_expression.rhs;
         let op = match half_binary_expression.op.as_str() {
             "&" => BinaryOp::And(
-                Arc::new(ExpressionWithWidth::new(lhs, &input.state)),
-                Arc::new(ExpressionWithWidth::new(rhs, &input.state)),
+                Arc::new(ExpressionWithWidth::new(lhs)),
+                Arc::new(ExpressionWithWidth::new(rhs)),
             ),
             "|" => BinaryOp::Or(
-                Arc::new(ExpressionWithWidth::new(lhs, &input.state)),
-                Arc::new(ExpressionWithWidth::new(rhs, &input.state)),
+                Arc::new(ExpressionWithWidth::new(lhs)),
+                Arc::new(ExpressionWithWidth::new(rhs)),
             ),
             "^" => BinaryOp::XOr(
-                Arc::new(ExpressionWithWidth::new(lhs, &input.state)),
-                Arc::new(ExpressionWithWidth::new(rhs, &input.state)),
+                Arc::new(ExpressionWithWidth::new(lhs)),
+                Arc::new(ExpressionWithWidth::new(rhs)),
             ),
             "!&" => BinaryOp::NAnd(
-                Arc::new(ExpressionWithWidth::new(lhs, &input.state)),
-                Arc::new(ExpressionWithWidth::new(rhs, &input.state)),
+                Arc::new(ExpressionWithWidth::new(lhs)),
+                Arc::new(ExpressionWithWidth::new(rhs)),
             ),
             "!|" => BinaryOp::NOr(
-                Arc::new(ExpressionWithWidth::new(lhs, &input.state)),
-                Arc::new(ExpressionWithWidth::new(rhs, &input.state)),
+                Arc::new(ExpressionWithWidth::new(lhs)),
+                Arc::new(ExpressionWithWidth::new(rhs)),
             ),
             "!^" => BinaryOp::XNOr(
-                Arc::new(ExpressionWithWidth::new(lhs, &input.state)),
-                Arc::new(ExpressionWithWidth::new(rhs, &input.state)),
+                Arc::new(ExpressionWithWidth::new(lhs)),
+                Arc::new(ExpressionWithWidth::new(rhs)),
             ),
 
             "%" => BinaryOp::Multiplex(
-                Arc::new(ExpressionWithWidth::new(lhs, &input.state)),
-                Arc::new(ExpressionWithWidth::new(rhs, &input.state)),
+                Arc::new(ExpressionWithWidth::new(lhs)),
+                Arc::new(ExpressionWithWidth::new(rhs)),
             ),
             _ => unreachable!(),
         };
@@ -203,7 +263,7 @@ fn parse_unary_expression(input: &mut Stream) -> PResult<Expression> {
 
     match op {
         "!" => Ok(Expression::UnaryOp(UnaryOp::Not(Arc::new(
-            ExpressionWithWidth::new(expr, &input.state),
+            ExpressionWithWidth::new(expr),
         )))),
         _ => unreachable!(),
     }
@@ -219,7 +279,7 @@ fn parse_range(input: &mut Stream) -> PResult<Range> {
     parse_whitespace(input)?;
 
     let (start, _, end) =
-        (parse_number_u32, parse_double_dot, parse_number_u32).parse_next(input)?;
+        (parse_const_expression, parse_double_dot, parse_const_expression).parse_next(input)?;
 
     Ok(Range { start, end })
 }
@@ -227,7 +287,7 @@ fn parse_range(input: &mut Stream) -> PResult<Range> {
 fn parse_bit_extract(input: &mut Stream) -> PResult<ExtractInner> {
     parse_whitespace(input)?;
 
-    parse_number_u32.map(ExtractInner::Bit).parse_next(input)
+    parse_index.map(ExtractInner::Bit).parse_next(input)
 }
 
 fn parse_name_extract(input: &mut Stream) -> PResult<ExtractInner> {
@@ -241,7 +301,7 @@ fn parse_name_extract(input: &mut Stream) -> PResult<ExtractInner> {
 fn parse_range_extract(input: &mut Stream) -> PResult<ExtractInner> {
     parse_whitespace(input)?;
 
-    (parse_number_u32, parse_double_dot, parse_number_u32)
+    (parse_index, parse_double_dot, parse_index)
         .map(|(start, _, end)| ExtractInner::Range(start, end))
         .parse_next(input)
 }
@@ -262,7 +322,8 @@ enum CombineKey {
 fn parse_multi_number(input: &mut Stream) -> PResult<Vec<u32>> {
     parse_whitespace(input)?;
 
-    let numbers = combinator::separated(1.., parse_number_u32, parse_comma).parse_next(input)?;
+    let numbers =
+        combinator::separated(1.., parse_const_expression, parse_comma).parse_next(input)?;
 
     Ok(numbers)
 }
@@ -282,8 +343,9 @@ fn parse_combine_key(input: &mut Stream) -> PResult<CombineKey> {
     combinator::terminated(
         combinator::alt((
             parse_range.map(CombineKey::NumberRange),
-            parse_multi_identifier.map(CombineKey::MultiIdentifier),
+            // numbers go first, a constant name is a number and not a key
             parse_multi_number.map(CombineKey::MultiNumber),
+            parse_multi_identifier.map(CombineKey::MultiIdentifier),
         )),
         parse_colon,
     )
@@ -306,13 +368,36 @@ fn parse_combine_kv(input: &mut Stream) -> PResult<CombineKV> {
     .parse_next(input)
 }
 
+fn parse_combine_kvs(input: &mut Stream) -> PResult<Vec<CombineKV>> {
+    parse_whitespace(input)?;
+
+    combinator::separated(0.., parse_combine_item, parse_comma)
+        .map(|v: Vec<Vec<_>>| v.into_iter().flatten().collect())
+        .parse_next(input)
+}
+
+fn parse_combine_for(input: &mut Stream) -> PResult<Vec<CombineKV>> {
+    parse_for(input, |input| {
+        combinator::terminated(
+            parse_combine_kvs,
+            (combinator::opt(parse_comma), parse_close_scope),
+        )
+        .parse_next(input)
+    })
+}
+
+fn parse_combine_item(input: &mut Stream) -> PResult<Vec<CombineKV>> {
+    parse_whitespace(input)?;
+
+    combinator::alt((parse_combine_for, parse_combine_kv.map(|kv| vec![kv]))).parse_next(input)
+}
+
 fn parse_combine_expression(input: &mut Stream) -> PResult<Expression> {
     parse_whitespace(input)?;
 
     parse_open_square_bracket(input)?;
 
-    let kvs: Vec<_> =
-        combinator::separated(0.., parse_combine_kv, parse_comma).parse_next(input)?;
+    let kvs = parse_combine_kvs(input)?;
 
     combinator::opt(parse_comma).parse_next(input)?; // optional trailing comma
 
@@ -342,10 +427,7 @@ fn parse_combine_expression(input: &mut Stream) -> PResult<Expression> {
                             map.insert(
                                 n,
                                 Expression::Extract(Extract {
-                                    expression: Arc::new(ExpressionWithWidth::new(
-                                        expression,
-                                        &input.state,
-                                    )),
+                                    expression: Arc::new(ExpressionWithWidth::new(expression)),
                                     extract: ExtractInner::Bit(i.try_into().unwrap()),
                                 }),
                             );
@@ -360,15 +442,9 @@ fn parse_combine_expression(input: &mut Stream) -> PResult<Expression> {
             let mut values = Vec::new();
             for i in 0..=largest {
                 if map.contains_key(&i) {
-                    values.push(ExpressionWithWidth::new(
-                        map.get(&i).unwrap().clone(),
-                        &input.state,
-                    ));
+                    values.push(ExpressionWithWidth::new(map.get(&i).unwrap().clone()));
                 } else {
-                    values.push(ExpressionWithWidth::new(
-                        Expression::Integer(0),
-                        &input.state,
-                    ));
+                    values.push(ExpressionWithWidth::new(Expression::Integer(0)));
                 }
             }
 
@@ -0,0 +1,139 @@
+use winnow::{combinator, stream::Stream as _, token, PResult, Parser};
+
+use super::{
+    constant::{parse_const_condition, parse_const_expression},
+    identifier::{parse_identifier, parse_keyword, parse_string},
+    trivial_tokens::{parse_double_dot, parse_open_scope},
+    whitespace::parse_whitespace,
+    Stream,
+};
+
+// skips everything up to and including the `}` that closes the current
+// block, without looking at what is inside. blocks that are not generated
+// can't be parsed, since they may refer to names that don't exist or compute
+// indices that are out of range.
+fn skip_block(input: &mut Stream) -> PResult<()> {
+    let mut depth = 1;
+
+    while depth > 0 {
+        parse_whitespace(input)?;
+
+        // braces inside strings don't count
+        if combinator::opt(parse_string).parse_next(input)?.is_some() {
+            continue;
+        }
+
+        match token::any.parse_next(input)? {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+// parses a block body with its own constant scope, `body` has to consume the
+// closing brace
+fn parse_generated_block<T, P>(input: &mut Stream, body: &mut P) -> PResult<Vec<T>>
+where
+    P: FnMut(&mut Stream) -> PResult<Vec<T>>,
+{
+    let constants_len = input.state.constants_len();
+    let result = body(input);
+    input.state.truncate_constants(constants_len);
+
+    result
+}
+
+// parses `for <name> in <start>..<end> { <body> }` and unrolls it right away:
+// the body is parsed once per iteration with `name` bound to the current
+// value, so everything after the parser only ever sees the expanded items.
+pub fn parse_for<T, P>(input: &mut Stream, mut body: P) -> PResult<Vec<T>>
+where
+    P: FnMut(&mut Stream) -> PResult<Vec<T>>,
+{
+    parse_whitespace(input)?;
+
+    parse_keyword(input, "for")?;
+    let variable = parse_identifier.map(|s| s.to_string()).parse_next(input)?;
+    parse_keyword(input, "in")?;
+
+    let start = parse_const_expression(input)?;
+    parse_double_dot(input)?;
+    let end = parse_const_expression(input)?;
+
+    parse_open_scope(input)?;
+
+    let mut items = vec![];
+
+    if start >= end {
+        skip_block(input)?;
+        return Ok(items);
+    }
+
+    let body_start = input.checkpoint();
+
+    for value in start..end {
+        input.reset(&body_start);
+
+        let constants_len = input.state.constants_len();
+        input.state.push_constant(variable.clone(), value);
+        let result = parse_generated_block(input, &mut body);
+        input.state.truncate_constants(constants_len);
+
+        items.extend(result?);
+    }
+
+    Ok(items)
+}
+
+// parses `if <condition> { ... } else if <condition> { ... } else { ... }`,
+// only the first branch whose condition holds is parsed, the rest is skipped
+pub fn parse_if<T, P>(input: &mut Stream, mut body: P) -> PResult<Vec<T>>
+where
+    P: FnMut(&mut Stream) -> PResult<Vec<T>>,
+{
+    parse_whitespace(input)?;
+
+    parse_keyword(input, "if")?;
+    let condition = parse_const_condition(input)?;
+    parse_open_scope(input)?;
+
+    let mut items = vec![];
+    let mut taken = false;
+
+    if condition {
+        items = parse_generated_block(input, &mut body)?;
+        taken = true;
+    } else {
+        skip_block(input)?;
+    }
+
+    while combinator::opt(|input: &mut Stream| parse_keyword(input, "else").map(|_| ()))
+        .parse_next(input)?
+        .is_some()
+    {
+        let condition = combinator::opt(combinator::preceded(
+            |input: &mut Stream| parse_keyword(input, "if").map(|_| ()),
+            parse_const_condition,
+        ))
+        .parse_next(input)?;
+
+        parse_open_scope(input)?;
+
+        if !taken && condition.unwrap_or(true) {
+            items = parse_generated_block(input, &mut body)?;
+            taken = true;
+        } else {
+            skip_block(input)?;
+        }
+
+        // a plain `else` ends the chain
+        if condition.is_none() {
+            break;
+        }
+    }
+
+    Ok(items)
+}
@@ -1,6 +1,13 @@
 use winnow::{combinator, token, PResult, Parser};
 
-use super::{trivial_tokens::{parse_backslash, parse_quote}, whitespace::parse_whitespace, Stream};
+use crate::utils::indexed_name::indexed_name;
+
+use super::{
+    constant::parse_const_expression,
+    trivial_tokens::{parse_backslash, parse_close_square_bracket, parse_quote},
+    whitespace::parse_whitespace,
+    Stream,
+};
 
 pub fn parse_identifier<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
     parse_whitespace(input)?;
@@ -12,6 +19,34 @@ pub fn parse_identifier<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
         .parse_next(input)
 }
 
+pub fn parse_keyword<'s>(input: &mut Stream<'s>, keyword: &str) -> PResult<&'s str> {
+    parse_identifier
+        .verify(|s: &str| s == keyword)
+        .parse_next(input)
+}
+
+// `name[index]` refers to the wire called `name[<value of index>]`, which
+// lets generated code give every iteration its own set of names
+pub fn parse_indexed_identifier(input: &mut Stream) -> PResult<String> {
+    parse_whitespace(input)?;
+
+    let name = parse_identifier(input)?;
+
+    // the bracket must directly follow the name, otherwise `a [0: b] % c`
+    // would be ambiguous
+    let index = combinator::opt(combinator::delimited(
+        "[",
+        parse_const_expression,
+        parse_close_square_bracket,
+    ))
+    .parse_next(input)?;
+
+    Ok(match index {
+        Some(index) => indexed_name(name, index),
+        None => name.to_string(),
+    })
+}
+
 fn parse_string_character(input: &mut Stream) -> PResult<char> {
     combinator::alt((
         token::none_of(['\n', '\r', '"', '\\']),
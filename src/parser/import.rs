@@ -0,0 +1,208 @@
+use std::{
+    fs, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use winnow::{combinator, PResult, Parser};
+
+use crate::{
+    preprocessor::Preprocessor,
+    types::program::{Program, ProgramStatement},
+};
+
+use super::{
+    identifier::{parse_identifier, parse_keyword, parse_string},
+    located_error, program,
+    whitespace::parse_whitespace,
+    ParseError, ParserOptions, ParserState, Stream,
+};
+
+// the modules and bundles a file defines
+fn defined_names(program: &Program) -> Vec<String> {
+    program
+        .statements
+        .iter()
+        .filter_map(|statement| match statement {
+            ProgramStatement::Module(module) => Some(module.name.clone()),
+            ProgramStatement::ExternalModule(module) => Some(module.name.clone()),
+            ProgramStatement::Bundle(bundle) => Some(bundle.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn parse_file(path: &Path, state: &mut ParserState) -> Result<Program, ParseError> {
+    let mut preprocessor = Preprocessor::new();
+    for (name, value) in &state.options.defines {
+        preprocessor.define(name, value);
+    }
+
+    let preprocessed = Rc::new(preprocessor.process_file(path).map_err(|e| ParseError {
+        location: e.location,
+        message: e.message,
+    })?);
+
+    let canonical = fs::canonicalize(path).map_err(|e| ParseError {
+        location: None,
+        message: format!("Cannot open {}: {}", path.display(), e),
+    })?;
+
+    state.file_stack.push(canonical.clone());
+    state.sources.push(preprocessed.clone());
+
+    let mut stream = Stream {
+        input: &preprocessed.source,
+        state: mem::replace(state, ParserState::new(ParserOptions::default())),
+    };
+
+    let result = program::parse_program(&mut stream);
+    let location = stream.state.location(stream.input.len());
+
+    *state = stream.state;
+    state.sources.pop();
+    state.file_stack.pop();
+
+    let program = result.map_err(|_| {
+        state.error.take().unwrap_or_else(|| ParseError {
+            location,
+            message: String::from("Syntax error"),
+        })
+    })?;
+
+    state
+        .loaded_files
+        .insert(canonical, defined_names(&program));
+
+    Ok(program)
+}
+
+// imports are looked up next to the importing file first, then in every
+// directory of the search path
+fn resolve_import(state: &ParserState, relative: &Path) -> Option<PathBuf> {
+    let importing_directory = state
+        .file_stack
+        .last()
+        .and_then(|file| file.parent())
+        .map(Path::to_path_buf);
+
+    importing_directory
+        .into_iter()
+        .chain(state.options.search_path.iter().cloned())
+        .map(|directory| directory.join(relative))
+        .find(|path| path.is_file())
+}
+
+enum ImportTarget {
+    File(String),
+    // `a::b::name` imports the module or bundle `name` from `a/b.dhl`
+    Module(Vec<String>),
+}
+
+// `import "path/to/file.dhl"` or `import path::to::file::module`. the modules
+// of an imported file are registered like any other module, and every file
+// is only loaded once no matter how often it is imported.
+pub fn parse_import(input: &mut Stream) -> PResult<Vec<ProgramStatement>> {
+    parse_whitespace(input)?;
+
+    parse_keyword(input, "import")?;
+
+    let target = combinator::alt((
+        parse_string.map(ImportTarget::File),
+        combinator::separated(2.., parse_identifier.map(|s| s.to_string()), "::")
+            .map(ImportTarget::Module),
+    ))
+    .parse_next(input)?;
+
+    if !input.state.is_top() {
+        return Err(located_error(
+            input,
+            String::from("Imports are only allowed at the top level"),
+        ));
+    }
+
+    let (relative_path, module) = match target {
+        ImportTarget::File(path) => (PathBuf::from(path), None),
+        ImportTarget::Module(mut path) => {
+            let module = path.pop();
+            (PathBuf::from(path.join("/") + ".dhl"), module)
+        }
+    };
+
+    let Some(path) = resolve_import(&input.state, &relative_path) else {
+        return Err(located_error(
+            input,
+            format!("Cannot find {} to import", relative_path.display()),
+        ));
+    };
+
+    let canonical = fs::canonicalize(&path).unwrap_or(path.clone());
+
+    if let Some(position) = input.state.file_stack.iter().position(|f| *f == canonical) {
+        let cycle = input.state.file_stack[position..]
+            .iter()
+            .chain([&canonical])
+            .map(|f| f.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        return Err(located_error(input, format!("Import cycle: {}", cycle)));
+    }
+
+    let statements = if input.state.loaded_files.contains_key(&canonical) {
+        // already loaded, its modules are registered already
+        vec![]
+    } else {
+        // the imported file gets a scope of its own, only its modules are
+        // shared with the importing file
+        let modules_stack = mem::take(&mut input.state.modules_stack);
+        let constants = mem::take(&mut input.state.constants);
+
+        let result = parse_file(&path, &mut input.state);
+
+        input.state.modules_stack = modules_stack;
+        input.state.constants = constants;
+
+        let program = match result {
+            Ok(program) => program,
+            Err(error) => {
+                input.state.error = Some(error);
+                return Err(winnow::error::ErrMode::Cut(
+                    winnow::error::ContextError::new(),
+                ));
+            }
+        };
+
+        let is_module = |statement: &ProgramStatement| {
+            matches!(
+                statement,
+                ProgramStatement::Module(_)
+                    | ProgramStatement::ExternalModule(_)
+                    | ProgramStatement::Bundle(_)
+            )
+        };
+
+        if !program.statements.iter().all(is_module) {
+            return Err(located_error(
+                input,
+                format!(
+                    "{} can't be imported, it contains statements outside of modules",
+                    path.display()
+                ),
+            ));
+        }
+
+        program.statements
+    };
+
+    if let Some(module) = module {
+        if !input.state.loaded_files[&canonical].contains(&module) {
+            return Err(located_error(
+                input,
+                format!("{} doesn't define {}", path.display(), module),
+            ));
+        }
+    }
+
+    Ok(statements)
+}
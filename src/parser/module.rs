@@ -6,31 +6,47 @@ use winnow::{
 
 use crate::{
     digital::{Coordinate, Entry, EntryValue, EntryValueDirection},
-    parser::ParserModuleVariable,
     types::{
         expression::Expression,
         module::{ExternalModule, ExternalModuleVariableData, Module},
+        source_info::SourceInfo,
     },
 };
 
 use super::{
     argument::parse_arguments,
+    constant::parse_const_expression,
     datatype::KnownBitWidth,
     identifier::{parse_identifier, parse_string},
     number::parse_signed_number,
-    program::parse_program_statement,
+    program::parse_program_statements,
     trivial_tokens::{
         parse_at, parse_close_paren, parse_close_scope, parse_colon, parse_comma, parse_down,
         parse_equals, parse_false, parse_left, parse_open_paren, parse_open_scope, parse_rgb,
         parse_rgba, parse_right, parse_star, parse_true, parse_up,
     },
-    whitespace::parse_whitespace,
-    ParserModuleVariableData, Stream,
+    whitespace::{allowed_lints, parse_whitespace},
+    Stream,
 };
 
+// parses the body of a module with a constant scope of its own. the scope is
+// closed even if the body fails to parse, so whatever is tried next starts
+// from the same state.
+fn parse_module_scope<T, P>(input: &mut Stream, body: P) -> PResult<T>
+where
+    P: FnOnce(&mut Stream) -> PResult<T>,
+{
+    input.state.start_new_module();
+    let result = body(input);
+    input.state.end_current_module();
+
+    result
+}
+
 enum ExternalModuleVariableType {
     Input,
     Output,
+    InOut,
 }
 
 fn parse_external_module_variable(
@@ -42,6 +58,7 @@ fn parse_external_module_variable(
         .map(|s| match s {
             "in" => ExternalModuleVariableType::Input,
             "out" => ExternalModuleVariableType::Output,
+            "inout" => ExternalModuleVariableType::InOut,
             _ => unreachable!(),
         })
         .parse_next(input)?;
@@ -59,11 +76,11 @@ fn parse_external_module_variable(
     } else {
         panic!("Bits argument must be a constant")
     };
-    let external_name = arguments.get("name").and_then(|arg| {
-        if let Expression::String(name) = arg.value.clone().expression {
-            Some(name)
+    let default = arguments.get("default").map(|arg| {
+        if let Expression::Integer(default) = arg.value.expression {
+            default
         } else {
-            None
+            panic!("Default argument must be a constant")
         }
     });
 
@@ -82,10 +99,10 @@ fn parse_external_module_variable(
 
     Ok((
         ExternalModuleVariableData {
-            name: variable_name.clone(),
-            external_name: external_name.unwrap_or_else(|| variable_name.clone()),
+            name: variable_name,
             width: bit_count,
             position,
+            default,
         },
         variable_type,
     ))
@@ -137,6 +154,7 @@ fn parse_entry_value(input: &mut Stream) -> PResult<EntryValue> {
 
     combinator::alt((
         parse_long.map(EntryValue::Long),
+        parse_const_expression.map(|v| EntryValue::Integer(v as i32)),
         parse_signed_number.map(|v| EntryValue::Integer(v as i32)),
         parse_string.map(|s| EntryValue::String(s.to_string())),
         parse_true.map(|_| EntryValue::Boolean(true)),
@@ -222,52 +240,34 @@ pub fn parse_external_module(input: &mut Stream) -> PResult<ExternalModule> {
 
     parse_open_scope(input)?;
 
-    input.state.start_new_module(rename_str.clone());
-
-    let body: Vec<_> =
+    let body: Vec<_> = parse_module_scope(input, |input| {
         combinator::repeat_till(0.., parse_external_module_body_item, parse_close_scope)
             .map(|v| v.0)
-            .parse_next(input)?;
+            .parse_next(input)
+    })?;
 
     let mut inputs = vec![];
     let mut outputs = vec![];
+    let mut inouts = vec![];
     let mut attributes = vec![];
 
     for item in body.iter() {
         match item {
             ExternalModuleBodyItem::Variable(data, ty) => match ty {
-                ExternalModuleVariableType::Input => {
-                    inputs.push(data.clone());
-                    input.state.add_variable(ParserModuleVariable::Input(
-                        ParserModuleVariableData {
-                            name: data.name.clone(),
-                            external_name: data.external_name.clone(),
-                            width: data.width.clone(),
-                        },
-                    ));
-                }
-                ExternalModuleVariableType::Output => {
-                    outputs.push(data.clone());
-                    input.state.add_variable(ParserModuleVariable::Output(
-                        ParserModuleVariableData {
-                            name: data.name.clone(),
-                            external_name: data.external_name.clone(),
-                            width: data.width.clone(),
-                        },
-                    ));
-                }
+                ExternalModuleVariableType::Input => inputs.push(data.clone()),
+                ExternalModuleVariableType::Output => outputs.push(data.clone()),
+                ExternalModuleVariableType::InOut => inouts.push(data.clone()),
             },
             ExternalModuleBodyItem::Attribute(entry) => attributes.push(entry.clone()),
         }
     }
 
-    input.state.end_current_module();
-
     Ok(ExternalModule {
         internal_name: name,
         name: rename_str,
         inputs,
         outputs,
+        inouts,
         attributes,
     })
 }
@@ -275,35 +275,28 @@ pub fn parse_external_module(input: &mut Stream) -> PResult<ExternalModule> {
 pub fn parse_module(input: &mut Stream) -> PResult<Module> {
     parse_whitespace(input)?;
 
+    let source = SourceInfo {
+        location: input.state.location(input.input.len()),
+        allowed: allowed_lints(input),
+    };
+
     let name = parse_identifier.map(|s| s.to_string()).parse_next(input)?;
 
     parse_open_scope(input)?;
 
-    input.state.start_new_module(name.clone());
-
-    let statements = combinator::repeat_till(0.., parse_program_statement, parse_close_scope)
-        .map(|v| v.0)
-        .parse_next(input)?;
-
-    let module = input.state.end_current_module();
-
-    let mut inputs = vec![];
-    let mut outputs = vec![];
-
-    for variable in module.variables {
-        match variable {
-            ParserModuleVariable::Input(data) => inputs.push(data),
-            ParserModuleVariable::Clock(data) => inputs.push(data),
-            ParserModuleVariable::Output(data) => outputs.push(data),
-            ParserModuleVariable::DefinedWire(_) => {}
-            ParserModuleVariable::UndefinedWire(_) => {}
-        }
-    }
+    let statements = parse_module_scope(input, |input| {
+        combinator::repeat_till(0.., parse_program_statements, parse_close_scope)
+            .map(|v: (Vec<Vec<_>>, _)| v.0.into_iter().flatten().collect())
+            .parse_next(input)
+    })?;
 
+    // inputs and outputs are filled in by the resolver
     Ok(Module {
         name,
         statements,
-        inputs,
-        outputs,
+        inputs: vec![],
+        outputs: vec![],
+        inouts: vec![],
+        source,
     })
 }
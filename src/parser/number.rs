@@ -37,7 +37,7 @@ pub fn parse_decimal_number(input: &mut Stream) -> PResult<u64> {
 
     token::take_while(1.., |c: char| c.is_ascii_digit() || c == '_')
         .parse_next(input)
-        .map(|s| u64::from_str_radix(&s.replace("_", ""), 10).unwrap())
+        .map(|s| s.replace("_", "").parse::<u64>().unwrap())
 }
 
 pub fn parse_number(input: &mut Stream) -> PResult<u64> {
@@ -6,36 +6,80 @@ use crate::{
 };
 
 use super::{
+    bundle::parse_bundle,
+    constant::parse_const_definition,
+    generate::{parse_for, parse_if},
+    import::parse_import,
+    located_error,
     module::{parse_external_module, parse_module},
-    variable_definition::parse_variable_definitions,
+    trivial_tokens::parse_close_scope,
+    variable_definition::{parse_destructuring, parse_variable_definitions},
     whitespace::parse_whitespace,
     Stream,
 };
 
 pub fn parse_program_statement(input: &mut Stream) -> PResult<ProgramStatement> {
-    combinator::alt((
+    let remaining = input.input.len();
+
+    let statement = combinator::alt((
         parse_external_module.map(ProgramStatement::ExternalModule),
+        parse_bundle.map(ProgramStatement::Bundle),
         parse_module.map(ProgramStatement::Module),
+        parse_destructuring.map(ProgramStatement::Destructuring),
         parse_variable_definitions.map(ProgramStatement::VariableDefinitions),
         parse_expression.map(ProgramStatement::Expression),
     ))
-    .parse_next(input)
+    .parse_next(input)?;
+
+    // an empty list of definitions reads nothing, the statements around it
+    // would never get any further
+    if input.input.len() == remaining {
+        return Err(located_error(input, String::from("Syntax error")));
+    }
+
+    Ok(statement)
 }
 
-pub fn parse_program(input: &mut Stream) -> PResult<Program> {
-    parse_whitespace(input)?;
+fn parse_for_statement(input: &mut Stream) -> PResult<Vec<ProgramStatement>> {
+    parse_for(input, |input| {
+        combinator::repeat_till(0.., parse_program_statements, parse_close_scope)
+            .map(|v: (Vec<Vec<_>>, _)| v.0.into_iter().flatten().collect())
+            .parse_next(input)
+    })
+}
 
-    let statements = combinator::repeat_till(0.., parse_program_statement, combinator::eof)
-        .map(|v| v.0)
-        .parse_next(input)?;
+fn parse_if_statement(input: &mut Stream) -> PResult<Vec<ProgramStatement>> {
+    parse_if(input, |input| {
+        combinator::repeat_till(0.., parse_program_statements, parse_close_scope)
+            .map(|v: (Vec<Vec<_>>, _)| v.0.into_iter().flatten().collect())
+            .parse_next(input)
+    })
+}
+
+// a single source statement can expand to any number of statements
+pub fn parse_program_statements(input: &mut Stream) -> PResult<Vec<ProgramStatement>> {
+    combinator::alt((
+        parse_import,
+        parse_for_statement,
+        parse_if_statement,
+        // constants are resolved while parsing and don't leave a statement
+        parse_const_definition.map(|_| vec![]),
+        parse_program_statement.map(|statement| vec![statement]),
+    ))
+    .parse_next(input)
+}
 
+pub fn parse_program(input: &mut Stream) -> PResult<Program> {
     parse_whitespace(input)?;
 
-    if input.len() > 0 {
-        return Err(winnow::error::ErrMode::Backtrack(
-            winnow::error::ContextError::new(),
-        ));
-    }
+    // trailing whitespace and comments are skipped before checking for the end
+    let statements = combinator::repeat_till(
+        0..,
+        parse_program_statements,
+        (parse_whitespace, combinator::eof),
+    )
+    .map(|v: (Vec<Vec<_>>, _)| v.0.into_iter().flatten().collect())
+    .parse_next(input)?;
 
     Ok(Program { statements })
 }
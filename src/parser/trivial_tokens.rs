@@ -22,6 +22,10 @@ pub fn parse_colon<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
     parse_multiple_chars(input, ":")
 }
 
+pub fn parse_semicolon<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, ";")
+}
+
 pub fn parse_comma<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
     parse_multiple_chars(input, ",")
 }
@@ -30,6 +34,38 @@ pub fn parse_equals<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
     parse_multiple_chars(input, "=")
 }
 
+pub fn parse_double_equals<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, "==")
+}
+
+pub fn parse_bang_equals<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, "!=")
+}
+
+pub fn parse_less_equals<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, "<=")
+}
+
+pub fn parse_greater_equals<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, ">=")
+}
+
+pub fn parse_less<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, "<")
+}
+
+pub fn parse_greater<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, ">")
+}
+
+pub fn parse_double_amperstand<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, "&&")
+}
+
+pub fn parse_double_pipe<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, "||")
+}
+
 pub fn parse_amperstand<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
     parse_multiple_chars(input, "&")
 }
@@ -84,6 +120,14 @@ pub fn parse_star<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
     parse_multiple_chars(input, "*")
 }
 
+pub fn parse_plus<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, "+")
+}
+
+pub fn parse_slash<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
+    parse_multiple_chars(input, "/")
+}
+
 pub fn parse_minus<'s>(input: &mut Stream<'s>) -> PResult<&'s str> {
     parse_multiple_chars(input, "-")
 }
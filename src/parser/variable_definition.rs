@@ -2,115 +2,144 @@ use winnow::{combinator, PResult, Parser};
 
 use crate::{
     parser::{
+        bundle::parse_bundle_field_type,
         decorator::parse_decorator,
         expression::parse_expression,
-        identifier::parse_identifier,
-        trivial_tokens::{parse_comma, parse_equals},
+        identifier::{parse_identifier, parse_indexed_identifier},
+        trivial_tokens::{
+            parse_close_paren, parse_close_scope, parse_colon, parse_comma, parse_equals,
+            parse_open_paren, parse_open_scope,
+        },
     },
     types::{
-        decorator::Decorator,
         expression::ExpressionWithWidth,
-        variable_definition::{VariableDefinition, VariableDefinitions},
+        source_info::SourceInfo,
+        variable_definition::{
+            DestructuredField, Destructuring, VariableDefinition, VariableDefinitions,
+        },
     },
 };
 
 use super::{
-    datatype::{GetBitWidth, KnownBitWidth},
-    whitespace::parse_whitespace,
-    ParserModuleVariable, ParserModuleVariableData, Stream,
+    whitespace::{allowed_lints, parse_whitespace},
+    Stream,
 };
 
 fn parse_variable_definition(input: &mut Stream) -> PResult<VariableDefinition> {
     parse_whitespace(input)?;
 
-    let name = parse_identifier.map(|s| s.to_string()).parse_next(input)?;
+    let name = parse_indexed_identifier(input)?;
+    let port_type = combinator::opt(combinator::preceded(parse_colon, parse_bundle_field_type))
+        .parse_next(input)?;
     let value =
         combinator::opt(combinator::preceded(parse_equals, parse_expression)).parse_next(input)?;
 
     Ok(VariableDefinition {
         name,
-        value: if let Some(value) = value {
-            Some(ExpressionWithWidth {
-                width: value.get_bit_width(&input.state),
-                expression: value,
-            })
-        } else {
-            None
-        },
+        port_type,
+        value: value.map(ExpressionWithWidth::new),
     })
 }
 
 pub fn parse_variable_definitions(input: &mut Stream) -> PResult<VariableDefinitions> {
     parse_whitespace(input)?;
 
-    let definitions = combinator::seq!(VariableDefinitions {
-        decorator: combinator::opt(parse_decorator),
-        definitions: combinator::separated(0.., parse_variable_definition, parse_comma)
-    })
-    .parse_next(input)?;
-
-    for definition in &definitions.definitions {
-        if let Some(ref decorator) = definitions.decorator {
-            let variable = match decorator {
-                Decorator::In(width, name) => {
-                    ParserModuleVariable::Input(ParserModuleVariableData {
-                        name: definition.name.clone(),
-                        external_name: name.clone().map_or(definition.name.clone(), |s| s),
-                        width: KnownBitWidth::Fixed(*width),
-                    })
-                }
-                Decorator::Out(width, name) => {
-                    ParserModuleVariable::Output(ParserModuleVariableData {
-                        name: definition.name.clone(),
-                        external_name: name.clone().map_or(definition.name.clone(), |s| s),
-                        width: if let Some(width) = width {
-                            KnownBitWidth::Fixed(*width)
-                        } else {
-                            definition
-                                .value
-                                .as_ref()
-                                .unwrap_or_else(|| {
-                                    panic!("Output variable {} has no value", definition.name)
-                                })
-                                .width
-                                .clone()
-                        },
-                    })
-                }
-                Decorator::Wire(width) => {
-                    ParserModuleVariable::UndefinedWire(ParserModuleVariableData {
-                        name: definition.name.clone(),
-                        external_name: definition.name.clone(),
-                        width: KnownBitWidth::Fixed(*width),
-                    })
-                }
-                Decorator::Clock(width) => {
-                    ParserModuleVariable::Clock(ParserModuleVariableData {
-                        name: definition.name.clone(),
-                        external_name: definition.name.clone(),
-                        width: KnownBitWidth::Fixed(1),
-                    })
-                }
-            };
-            input.state.add_variable(variable);
-        } else {
-            let expression = definition.value.as_ref();
-
-            if let Some(expression) = expression {
-                input.state.add_variable(ParserModuleVariable::DefinedWire(
-                    ParserModuleVariableData {
-                        name: definition.name.clone(),
-                        external_name: definition.name.clone(),
-                        width: expression.width.clone(),
-                    },
-                ));
-            } else {
-                return Err(winnow::error::ErrMode::Backtrack(
-                    winnow::error::ContextError::new(),
-                ));
-            }
-        }
+    let source = SourceInfo {
+        location: input.state.location(input.input.len()),
+        allowed: allowed_lints(input),
+    };
+
+    let decorator = combinator::opt(parse_decorator).parse_next(input)?;
+    let definitions =
+        combinator::separated(0.., parse_variable_definition, parse_comma).parse_next(input)?;
+
+    let definitions = VariableDefinitions {
+        definitions,
+        decorator,
+        source,
+    };
+
+    // without a decorator, every definition needs a value. only those can
+    // have a type, a decorator brings its own.
+    let invalid = |definition: &VariableDefinition| match definitions.decorator {
+        None => definition.value.is_none(),
+        Some(_) => definition.port_type.is_some(),
+    };
+
+    if definitions.definitions.iter().any(invalid) {
+        return Err(winnow::error::ErrMode::Backtrack(
+            winnow::error::ContextError::new(),
+        ));
     }
 
     Ok(definitions)
 }
+
+// `(sum, carry)`
+fn parse_positional_fields(input: &mut Stream) -> PResult<Vec<DestructuredField>> {
+    parse_whitespace(input)?;
+
+    combinator::delimited(
+        parse_open_paren,
+        combinator::separated(
+            1..,
+            parse_indexed_identifier.map(|name| DestructuredField { output: None, name }),
+            parse_comma,
+        ),
+        parse_close_paren,
+    )
+    .parse_next(input)
+}
+
+// `sum: s`, or just `sum` to keep the name of the output
+fn parse_named_field(input: &mut Stream) -> PResult<DestructuredField> {
+    parse_whitespace(input)?;
+
+    let output = parse_identifier.map(|s| s.to_string()).parse_next(input)?;
+    let name = combinator::opt(combinator::preceded(parse_colon, parse_indexed_identifier))
+        .parse_next(input)?;
+
+    Ok(DestructuredField {
+        name: name.unwrap_or_else(|| output.clone()),
+        output: Some(output),
+    })
+}
+
+// `{sum: s, carry: c}`
+fn parse_named_fields(input: &mut Stream) -> PResult<Vec<DestructuredField>> {
+    parse_whitespace(input)?;
+
+    combinator::delimited(
+        parse_open_scope,
+        combinator::terminated(
+            combinator::separated(1.., parse_named_field, parse_comma),
+            combinator::opt(parse_comma), // optional trailing comma
+        ),
+        parse_close_scope,
+    )
+    .parse_next(input)
+}
+
+pub fn parse_destructuring(input: &mut Stream) -> PResult<Destructuring> {
+    parse_whitespace(input)?;
+
+    let source = SourceInfo {
+        location: input.state.location(input.input.len()),
+        allowed: allowed_lints(input),
+    };
+
+    let (decorator, fields, _, value) = (
+        combinator::opt(parse_decorator),
+        combinator::alt((parse_positional_fields, parse_named_fields)),
+        parse_equals,
+        parse_expression.map(ExpressionWithWidth::new),
+    )
+        .parse_next(input)?;
+
+    Ok(Destructuring {
+        fields,
+        value,
+        decorator,
+        source,
+    })
+}
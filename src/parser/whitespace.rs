@@ -30,3 +30,20 @@ pub fn parse_whitespace(input: &mut Stream) -> PResult<()> {
         }
     }
 }
+
+// the lints turned off by an `// allow(width, ...)` comment at the end of
+// the current line
+pub fn allowed_lints(input: &Stream) -> Vec<String> {
+    let line = input.input.split('\n').next().unwrap_or_default();
+
+    line.split_once("//")
+        .and_then(|(_, comment)| comment.trim().strip_prefix("allow("))
+        .and_then(|lints| lints.strip_suffix(')'))
+        .map(|lints| {
+            lints
+                .split(',')
+                .map(|lint| lint.trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
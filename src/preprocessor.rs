@@ -0,0 +1,260 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use macros::{expand_macros, Macro};
+
+mod macros;
+mod source_map;
+
+pub use source_map::{SourceLocation, SourceMap};
+
+#[derive(Debug)]
+pub struct PreprocessorError {
+    pub location: Option<SourceLocation>,
+    pub message: String,
+}
+
+impl fmt::Display for PreprocessorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: {}", location, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Preprocessed {
+    pub source: String,
+    pub source_map: SourceMap,
+}
+
+struct Conditional {
+    // whether the lines of the current branch are kept
+    active: bool,
+    // whether the enclosing block is kept at all
+    parent_active: bool,
+    has_else: bool,
+    location: SourceLocation,
+}
+
+// runs before the parser. it handles `#define`, `#undef`, `#ifdef`,
+// `#ifndef`, `#else`, `#endif` and `#include`, and expands macros. directives
+// are replaced with empty lines, so every output line still corresponds to
+// exactly one line of some input file.
+pub struct Preprocessor {
+    macros: HashMap<String, Macro>,
+    include_stack: Vec<PathBuf>,
+
+    output: String,
+    source_map: SourceMap,
+}
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Self {
+            macros: HashMap::new(),
+            include_stack: vec![],
+
+            output: String::new(),
+            source_map: SourceMap::new(),
+        }
+    }
+
+    // defines a macro from outside the source, e.g. from the command line
+    pub fn define(&mut self, name: &str, value: &str) {
+        self.macros.insert(
+            name.to_string(),
+            Macro {
+                parameters: None,
+                body: value.to_string(),
+            },
+        );
+    }
+
+    pub fn process_file(mut self, path: &Path) -> Result<Preprocessed, PreprocessorError> {
+        self.include(path, None)?;
+
+        Ok(Preprocessed {
+            source: self.output,
+            source_map: self.source_map,
+        })
+    }
+
+    fn include(
+        &mut self,
+        path: &Path,
+        included_from: Option<SourceLocation>,
+    ) -> Result<(), PreprocessorError> {
+        let canonical = fs::canonicalize(path).map_err(|e| PreprocessorError {
+            location: included_from.clone(),
+            message: format!("Cannot open {}: {}", path.display(), e),
+        })?;
+
+        if self.include_stack.contains(&canonical) {
+            return Err(PreprocessorError {
+                location: included_from,
+                message: format!("{} includes itself", path.display()),
+            });
+        }
+
+        let contents = fs::read_to_string(path).map_err(|e| PreprocessorError {
+            location: included_from.clone(),
+            message: format!("Cannot read {}: {}", path.display(), e),
+        })?;
+
+        self.include_stack.push(canonical);
+        let result = self.process_source(path, &contents);
+        self.include_stack.pop();
+
+        result
+    }
+
+    fn push_line(&mut self, line: &str, location: SourceLocation) {
+        self.output.push_str(line);
+        self.output.push('\n');
+        self.source_map.push_line(location);
+    }
+
+    fn process_source(&mut self, path: &Path, contents: &str) -> Result<(), PreprocessorError> {
+        let file = path.display().to_string();
+        let mut conditionals: Vec<Conditional> = vec![];
+
+        for (i, line) in contents.lines().enumerate() {
+            let location = SourceLocation {
+                file: file.clone(),
+                line: i + 1,
+            };
+            let error = |message: String| PreprocessorError {
+                location: Some(location.clone()),
+                message,
+            };
+
+            let active = conditionals.last().is_none_or(|c| c.active);
+            let trimmed = line.trim_start();
+
+            let Some(directive) = trimmed.strip_prefix('#') else {
+                if active {
+                    let expanded = expand_macros(line, &self.macros).map_err(error)?;
+                    self.push_line(&expanded, location);
+                } else {
+                    self.push_line("", location);
+                }
+                continue;
+            };
+
+            // directives are kept as empty lines to keep line numbers intact
+            self.push_line("", location.clone());
+
+            let directive = directive.trim();
+            let (name, argument) = directive
+                .split_once(char::is_whitespace)
+                .map_or((directive, ""), |(name, argument)| (name, argument.trim()));
+
+            match name {
+                "ifdef" | "ifndef" => {
+                    let defined = self.macros.contains_key(argument);
+
+                    conditionals.push(Conditional {
+                        active: active && (defined == (name == "ifdef")),
+                        parent_active: active,
+                        has_else: false,
+                        location,
+                    });
+                }
+                "else" => {
+                    let conditional = conditionals
+                        .last_mut()
+                        .ok_or_else(|| error(String::from("#else without #ifdef")))?;
+
+                    if conditional.has_else {
+                        return Err(error(String::from("Duplicate #else")));
+                    }
+
+                    conditional.has_else = true;
+                    conditional.active = conditional.parent_active && !conditional.active;
+                }
+                "endif" => {
+                    conditionals
+                        .pop()
+                        .ok_or_else(|| error(String::from("#endif without #ifdef")))?;
+                }
+                _ if !active => {}
+                "define" => {
+                    let (name, definition) = parse_define(argument).map_err(error)?;
+                    self.macros.insert(name, definition);
+                }
+                "undef" => {
+                    self.macros.remove(argument);
+                }
+                "include" => {
+                    let included = argument
+                        .strip_prefix('"')
+                        .and_then(|s| s.strip_suffix('"'))
+                        .ok_or_else(|| {
+                            error(String::from("Expected a quoted path after #include"))
+                        })?;
+
+                    // paths are relative to the including file
+                    let included = path
+                        .parent()
+                        .map_or_else(|| PathBuf::from(included), |dir| dir.join(included));
+
+                    self.include(&included, Some(location))?;
+                }
+                _ => return Err(error(format!("Unknown directive #{}", name))),
+            }
+        }
+
+        if let Some(conditional) = conditionals.pop() {
+            return Err(PreprocessorError {
+                location: Some(conditional.location),
+                message: String::from("Unterminated #ifdef"),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// `NAME body` or `NAME(a, b) body`. the parameter list has to directly follow
+// the name, `NAME (a)` defines an object-like macro with the body `(a)`.
+fn parse_define(argument: &str) -> Result<(String, Macro), String> {
+    let name_end = argument
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(argument.len());
+    let name = &argument[..name_end];
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(String::from("Expected a macro name after #define"));
+    }
+
+    let rest = &argument[name_end..];
+
+    let (parameters, body) = if let Some(rest) = rest.strip_prefix('(') {
+        let (parameters, body) = rest
+            .split_once(')')
+            .ok_or_else(|| format!("Unterminated parameter list of macro {}", name))?;
+
+        let parameters = parameters
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect();
+
+        (Some(parameters), body)
+    } else {
+        (None, rest)
+    };
+
+    Ok((
+        name.to_string(),
+        Macro {
+            parameters,
+            body: body.trim().to_string(),
+        },
+    ))
+}
@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone)]
+pub struct Macro {
+    // `None` for object-like macros, which are used without parentheses
+    pub parameters: Option<Vec<String>>,
+    pub body: String,
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+// splits the arguments of a macro call, `chars[start]` must be the opening
+// parenthesis. returns the arguments and the index after the closing one.
+fn parse_macro_arguments(chars: &[char], start: usize) -> Result<(Vec<String>, usize), String> {
+    let mut arguments = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        match c {
+            '(' | '[' | '{' => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' if depth == 0 => {
+                arguments.push(current.trim().to_string());
+                return Ok((arguments, i + 1));
+            }
+            ')' | ']' | '}' => {
+                depth -= 1;
+                current.push(c);
+            }
+            ',' if depth == 0 => {
+                arguments.push(current.trim().to_string());
+                current = String::new();
+            }
+            '"' => {
+                // strings are copied as they are, commas inside don't count
+                current.push(c);
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        current.push(chars[i]);
+                        i += 1;
+                    }
+                    current.push(chars[i]);
+                    i += 1;
+                }
+                if i < chars.len() {
+                    current.push(chars[i]);
+                }
+            }
+            _ => current.push(c),
+        }
+
+        i += 1;
+    }
+
+    Err(String::from("Unterminated macro arguments"))
+}
+
+fn substitute_parameters(body: &str, parameters: &[String], arguments: &[String]) -> String {
+    let chars: Vec<char> = body.chars().collect();
+    let mut output = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if is_identifier_start(chars[i]) {
+            let start = i;
+            while i < chars.len() && is_identifier_char(chars[i]) {
+                i += 1;
+            }
+
+            let identifier: String = chars[start..i].iter().collect();
+
+            match parameters.iter().position(|p| *p == identifier) {
+                Some(index) => output.push_str(&arguments[index]),
+                None => output.push_str(&identifier),
+            }
+        } else {
+            output.push(chars[i]);
+            i += 1;
+        }
+    }
+
+    output
+}
+
+fn expand(
+    line: &str,
+    macros: &HashMap<String, Macro>,
+    expanding: &mut HashSet<String>,
+) -> Result<String, String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut output = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '"' {
+            // copy strings verbatim
+            output.push(c);
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    output.push(chars[i]);
+                    i += 1;
+                }
+                output.push(chars[i]);
+                i += 1;
+            }
+            if i < chars.len() {
+                output.push(chars[i]);
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            // the rest of the line is a comment
+            output.extend(&chars[i..]);
+            break;
+        } else if c.is_ascii_digit() {
+            // numbers like 0x1f must not be mistaken for identifiers
+            while i < chars.len() && is_identifier_char(chars[i]) {
+                output.push(chars[i]);
+                i += 1;
+            }
+        } else if is_identifier_start(c) {
+            let start = i;
+            while i < chars.len() && is_identifier_char(chars[i]) {
+                i += 1;
+            }
+
+            let identifier: String = chars[start..i].iter().collect();
+
+            let Some(definition) = macros
+                .get(&identifier)
+                .filter(|_| !expanding.contains(&identifier))
+            else {
+                output.push_str(&identifier);
+                continue;
+            };
+
+            let replacement = match &definition.parameters {
+                None => definition.body.clone(),
+                Some(parameters) => {
+                    let mut open = i;
+                    while open < chars.len() && chars[open].is_whitespace() {
+                        open += 1;
+                    }
+
+                    // a function-like macro without arguments is left alone
+                    if chars.get(open) != Some(&'(') {
+                        output.push_str(&identifier);
+                        continue;
+                    }
+
+                    let (arguments, end) = parse_macro_arguments(&chars, open)?;
+                    i = end;
+
+                    // `NAME()` is a call without arguments
+                    let arguments = if parameters.is_empty() && arguments == [""] {
+                        vec![]
+                    } else {
+                        arguments
+                    };
+
+                    if arguments.len() != parameters.len() {
+                        return Err(format!(
+                            "Macro {} expects {} arguments, but {} were given",
+                            identifier,
+                            parameters.len(),
+                            arguments.len()
+                        ));
+                    }
+
+                    substitute_parameters(&definition.body, parameters, &arguments)
+                }
+            };
+
+            // the result is expanded again, but a macro never expands itself
+            expanding.insert(identifier.clone());
+            let expanded = expand(&replacement, macros, expanding);
+            expanding.remove(&identifier);
+
+            output.push_str(&expanded?);
+        } else {
+            output.push(c);
+            i += 1;
+        }
+    }
+
+    Ok(output)
+}
+
+pub fn expand_macros(line: &str, macros: &HashMap<String, Macro>) -> Result<String, String> {
+    if macros.is_empty() {
+        return Ok(line.to_string());
+    }
+
+    expand(line, macros, &mut HashSet::new())
+}
@@ -0,0 +1,45 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+// maps every line of the preprocessed source back to the line of the file it
+// came from. the preprocessor never joins or splits lines, so one entry per
+// output line is enough.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    lines: Vec<SourceLocation>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self { lines: vec![] }
+    }
+
+    pub fn push_line(&mut self, location: SourceLocation) {
+        self.lines.push(location);
+    }
+
+    // `line` is zero based, as counted in the preprocessed source
+    pub fn location(&self, line: usize) -> Option<&SourceLocation> {
+        self.lines.get(line).or_else(|| self.lines.last())
+    }
+
+    // finds the original location of a byte offset into the preprocessed
+    // source
+    pub fn locate(&self, source: &str, offset: usize) -> Option<&SourceLocation> {
+        let offset = offset.min(source.len());
+        let line = source[..offset].matches('\n').count();
+
+        self.location(line)
+    }
+}
@@ -0,0 +1,541 @@
+use std::{cell::RefCell, collections::HashMap, mem, process, sync::Arc};
+
+use crate::{
+    lint::{LintOptions, Warning},
+    parser::{datatype::KnownBitWidth, ParserModuleVariableData},
+    types::{
+        bundle::Bundle,
+        decorator::Decorator,
+        module::Module,
+        program::{Program, ProgramStatement},
+        source_info::SourceInfo,
+        variable_definition::VariableDefinitions,
+    },
+    utils::indexed_name::{indexed_name, split_indexed_name},
+};
+
+mod bundle;
+mod expression;
+mod loops;
+mod module;
+mod program;
+mod usage;
+mod variable_definition;
+
+// what drives a wire that was declared with `@wire` or `@inout`
+#[derive(Debug, Clone)]
+pub enum WireDriver {
+    Plain,
+    // `drive(value, enable)`, with the value of `enable` if it is a constant
+    TriState(Option<u32>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ResolverOptions {
+    // every width warning is an error, and so is extending a value that
+    // isn't a constant
+    pub strict_widths: bool,
+    // warns about every value that is zero-extended, not only about cut off
+    // bits
+    pub warn_widening: bool,
+    pub lints: LintOptions,
+}
+
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub inputs: Vec<ParserModuleVariableData>,
+    pub inouts: Vec<ParserModuleVariableData>,
+    // the width of the object returned by a use of the module
+    pub outputs: KnownBitWidth,
+    // the outputs in the order they are declared
+    pub output_names: Vec<String>,
+    // the inputs every output is computed from without going through
+    // storage, empty for external modules
+    pub paths: HashMap<String, Vec<String>>,
+}
+
+// how much of a variable is read
+#[derive(Debug, Clone, PartialEq)]
+enum Used {
+    Nothing,
+    // only these fields of an object
+    Fields(Vec<String>),
+    All,
+}
+
+#[derive(Clone)]
+struct Variable {
+    name: String,
+    width: KnownBitWidth,
+    // `None` for variables that can't be assigned to after their definition
+    drivers: Option<Vec<WireDriver>>,
+    // ports are read and driven outside of the module, they are never
+    // reported as unused or undriven
+    port: bool,
+    used: Used,
+    // the statement that defines it
+    source: SourceInfo,
+}
+
+// the widths of the `@wire`s without a width in the statements that are
+// being resolved, see resolve_statements
+#[derive(Default)]
+struct Inference {
+    widths: HashMap<String, u32>,
+    // every value assigned to a declared wire, if it is a constant and the
+    // statement that assigns it
+    assignments: Vec<(String, KnownBitWidth, bool, SourceInfo)>,
+    // the statements are only resolved to find the widths
+    sizing: bool,
+}
+
+pub trait Resolve {
+    // fills in the bit widths of everything inside, returns the width of the
+    // value itself
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth;
+}
+
+// runs after parsing. it infers the bit width of every expression and the
+// inputs and outputs of every module. all modules are known before anything
+// is resolved, so a module can be used above its definition.
+pub struct Resolver {
+    options: ResolverOptions,
+    // the statement that is being resolved, warnings point to it
+    source: SourceInfo,
+    warnings: RefCell<Vec<Warning>>,
+
+    // the global variables, followed by the variables of the module that is
+    // being resolved. a module only sees its own variables.
+    scopes: Vec<Vec<Variable>>,
+    // one for every list of statements that is being resolved
+    inference: Vec<Inference>,
+
+    // modules that haven't been resolved yet
+    pending: HashMap<String, Module>,
+    signatures: HashMap<String, Signature>,
+    // modules that are being resolved right now, a module can't use any of them
+    resolving: Vec<String>,
+
+    bundles: HashMap<String, Bundle>,
+    bundle_widths: HashMap<String, KnownBitWidth>,
+    resolving_bundles: Vec<String>,
+
+    // the names of all modules in the order they were defined
+    order: Vec<String>,
+    resolved: HashMap<String, ProgramStatement>,
+}
+
+impl Resolver {
+    fn new(options: ResolverOptions) -> Self {
+        Self {
+            options,
+            source: SourceInfo::default(),
+            warnings: RefCell::new(vec![]),
+
+            scopes: vec![vec![]],
+            inference: vec![],
+
+            pending: HashMap::new(),
+            signatures: HashMap::new(),
+            resolving: vec![],
+
+            bundles: HashMap::new(),
+            bundle_widths: HashMap::new(),
+            resolving_bundles: vec![],
+
+            order: vec![],
+            resolved: HashMap::new(),
+        }
+    }
+
+    fn push_variable(&mut self, name: String, width: KnownBitWidth, port: bool, wire: bool) {
+        if self.find(&name).is_some() {
+            self.error(format!("{} is defined more than once", name));
+        }
+
+        let source = self.source.clone();
+
+        self.scopes.last_mut().unwrap().push(Variable {
+            name,
+            width,
+            drivers: wire.then(Vec::new),
+            port,
+            used: Used::Nothing,
+            source,
+        });
+    }
+
+    pub fn add_variable(&mut self, name: String, width: KnownBitWidth, port: bool) {
+        self.push_variable(name, width, port, false);
+    }
+
+    // a wire that is defined later on, possibly by several tri-state drivers
+    pub fn add_declared_wire(&mut self, name: String, width: KnownBitWidth, port: bool) {
+        self.push_variable(name, width, port, true);
+    }
+
+    fn find(&mut self, name: &str) -> Option<&mut Variable> {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|variable| variable.name == name)
+    }
+
+    // a variable read by an expression, or only one field of it
+    pub fn read_variable(&mut self, name: &str, field: Option<&str>) -> KnownBitWidth {
+        let Some(width) = self.find_variable(name) else {
+            self.missing_variable(name);
+        };
+
+        self.mark_used(name, field);
+
+        width
+    }
+
+    fn mark_used(&mut self, name: &str, field: Option<&str>) {
+        if let Some(variable) = self.find(name) {
+            variable.used = match (&variable.used, field) {
+                (Used::All, _) | (_, None) => Used::All,
+                (Used::Nothing, Some(field)) => Used::Fields(vec![field.to_string()]),
+                (Used::Fields(fields), Some(field)) => {
+                    Used::Fields([fields.clone(), vec![field.to_string()]].concat())
+                }
+            };
+
+            return;
+        }
+
+        // an element of an array, or an array of elements
+        if let Some((array, _)) = split_indexed_name(name) {
+            if self.find_variable(array).is_some() {
+                self.mark_used(array, None);
+                return;
+            }
+        }
+
+        for index in 0.. {
+            let element = indexed_name(name, index);
+
+            if self.find(&element).is_none() {
+                break;
+            }
+
+            self.mark_used(&element, None);
+        }
+    }
+
+    // a module has to declare every global variable it reads with `@global`
+    fn find_global(&mut self, name: &str) -> KnownBitWidth {
+        let Some(module) = self.resolving.last().cloned() else {
+            panic!("@global {} can only be used inside a module", name);
+        };
+
+        let variables = self.scopes.pop().unwrap();
+        let width = self.find_variable(name);
+        if width.is_some() {
+            self.mark_used(name, None);
+        }
+        self.scopes.push(variables);
+
+        width.unwrap_or_else(|| {
+            self.error(format!(
+                "Module {} reads the global {}, which is neither an input, a clock or a declared wire of the top level nor defined before the module is first used",
+                module, name
+            ))
+        })
+    }
+
+    // the error for a variable that isn't defined, or that a module can't see
+    pub fn missing_variable(&mut self, name: &str) -> ! {
+        if let Some(module) = self.resolving.last().cloned() {
+            let variables = self.scopes.pop().unwrap();
+            let global = self.find_variable(name).is_some();
+            self.scopes.push(variables);
+
+            if global {
+                self.error(format!(
+                    "Module {} reads the global {}, it has to be declared with @global {}",
+                    module, name, name
+                ));
+            }
+        }
+
+        self.error(format!("Variable {} not found", name))
+    }
+
+    // `out[3]` is also element 3 of an array called `out`, and `out` is also
+    // the array of `out[0]`, `out[1]`, ... if those were defined one by one
+    pub fn find_variable(&mut self, name: &str) -> Option<KnownBitWidth> {
+        if let Some(variable) = self.find(name) {
+            return Some(variable.width.clone());
+        }
+
+        if let Some((array, index)) = split_indexed_name(name) {
+            if let Some(KnownBitWidth::Array(elements)) = self.find_variable(array) {
+                let element = elements.get(index as usize).unwrap_or_else(|| {
+                    panic!(
+                        "Index {} is out of range, {} has {} elements",
+                        index,
+                        array,
+                        elements.len()
+                    )
+                });
+
+                return Some(element.as_ref().clone());
+            }
+        }
+
+        let elements: Vec<_> = (0..)
+            .map_while(|index| {
+                self.find(&indexed_name(name, index))
+                    .map(|element| Arc::new(element.width.clone()))
+            })
+            .collect();
+
+        if elements.is_empty() {
+            None
+        } else {
+            Some(KnownBitWidth::Array(elements))
+        }
+    }
+
+    // the elements of a declared array can be driven one by one, each of them
+    // becomes a declared wire the first time it is driven
+    fn declare_element(&mut self, name: &str) {
+        let Some((array, index)) = split_indexed_name(name) else {
+            return;
+        };
+
+        if self.find(array).is_none() {
+            self.declare_element(array);
+        }
+
+        let Some(Variable {
+            width: KnownBitWidth::Array(elements),
+            drivers: Some(_),
+            ..
+        }) = self.find(array)
+        else {
+            return;
+        };
+
+        let width = elements
+            .get(index as usize)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Index {} is out of range, {} has {} elements",
+                    index,
+                    array,
+                    elements.len()
+                )
+            })
+            .as_ref()
+            .clone();
+
+        self.add_declared_wire(name.to_string(), width, false);
+    }
+
+    // records a definition of a declared wire, returns false if `name` isn't
+    // one. a wire with several drivers is a bus, every driver has to be
+    // tri-state and at most one of them can be enabled at a time.
+    pub fn add_driver(&mut self, name: &str, driver: WireDriver) -> bool {
+        if self.find(name).is_none() {
+            self.declare_element(name);
+        }
+
+        let Some(drivers) = self.find(name).and_then(|v| v.drivers.as_mut()) else {
+            return false;
+        };
+
+        drivers.push(driver);
+
+        if drivers.len() > 1 {
+            if drivers.iter().any(|d| matches!(d, WireDriver::Plain)) {
+                panic!(
+                    "{} has more than one driver, all of them have to use drive()",
+                    name
+                );
+            }
+
+            let always_enabled = drivers
+                .iter()
+                .filter(|d| matches!(d, WireDriver::TriState(Some(enable)) if *enable != 0))
+                .count();
+
+            if always_enabled > 1 {
+                panic!("More than one driver of {} is always enabled", name);
+            }
+        }
+
+        true
+    }
+
+    // true while the statements are only resolved to infer the widths of
+    // wires, see resolve_statements
+    pub fn sizing(&self) -> bool {
+        self.inference
+            .last()
+            .is_some_and(|inference| inference.sizing)
+    }
+
+    // reports a problem with the statement that is being resolved, unless
+    // the statement allows the lint
+    pub fn warn(&self, lint: &'static str, message: String) {
+        // the statements are resolved again once the widths are known
+        if self.sizing() {
+            return;
+        }
+
+        self.warn_at(&self.source, lint, message);
+    }
+
+    // an error in the statement that is being resolved, reported with its
+    // file and line like a parse error
+    pub fn error(&self, message: String) -> ! {
+        self.error_at(&self.source, message)
+    }
+
+    fn error_at(&self, source: &SourceInfo, message: String) -> ! {
+        match &source.location {
+            Some(location) => eprintln!("error: {}: {}", location, message),
+            None => eprintln!("error: {}", message),
+        }
+
+        process::exit(1);
+    }
+
+    // width warnings are errors in strict mode
+    fn warn_at(&self, source: &SourceInfo, lint: &'static str, message: String) {
+        let Some(warning) = self.options.lints.report(source, lint, message) else {
+            return;
+        };
+
+        if lint == "width" && self.options.strict_widths {
+            self.error_at(source, warning.message);
+        }
+
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    // the inputs and outputs of a module, resolving the module first if it
+    // hasn't been resolved yet
+    pub fn signature(&mut self, name: &str) -> Signature {
+        if let Some(signature) = self.signatures.get(name) {
+            return signature.clone();
+        }
+
+        if self.resolving.iter().any(|module| module == name) {
+            panic!("Module {} uses itself", name);
+        }
+
+        let module = self
+            .pending
+            .remove(name)
+            .unwrap_or_else(|| panic!("Module {} not found", name));
+
+        // modules can read the global variables they declare, but never the
+        // ones of the module that happens to use them first
+        let scopes: Vec<_> = self.scopes.drain(1..).collect();
+
+        self.resolve_module(module);
+
+        self.scopes.extend(scopes);
+
+        self.signatures[name].clone()
+    }
+
+    fn define_module(&mut self, name: &str) {
+        if self.order.iter().any(|module| module == name) {
+            panic!("Module {} is defined more than once", name);
+        }
+
+        self.order.push(name.to_string());
+    }
+
+    // takes every module and bundle out of the statements, nested ones
+    // included
+    fn collect_modules(&mut self, statements: &mut Vec<ProgramStatement>) {
+        for statement in mem::take(statements) {
+            match statement {
+                ProgramStatement::Module(mut module) => {
+                    self.collect_modules(&mut module.statements);
+
+                    self.define_module(&module.name);
+                    self.pending.insert(module.name.clone(), module);
+                }
+                ProgramStatement::ExternalModule(module) => {
+                    self.define_module(&module.name);
+                    self.resolve_external_module(module);
+                }
+                // bundles only matter to the resolver, they don't end up in
+                // the resolved program
+                ProgramStatement::Bundle(bundle) => {
+                    if self.bundles.contains_key(&bundle.name) {
+                        panic!("Bundle {} is defined more than once", bundle.name);
+                    }
+
+                    self.bundles.insert(bundle.name.clone(), bundle);
+                }
+                statement => statements.push(statement),
+            }
+        }
+    }
+}
+
+// the top-level inputs, clocks and declared wires, they don't depend on any
+// other statement
+fn is_declaration(statement: &ProgramStatement) -> bool {
+    let ProgramStatement::VariableDefinitions(VariableDefinitions {
+        definitions,
+        decorator,
+        ..
+    }) = statement
+    else {
+        return false;
+    };
+
+    match decorator {
+        Some(Decorator::In(..)) | Some(Decorator::Clock(_)) => true,
+        Some(Decorator::InOut(..)) | Some(Decorator::Wire(Some(_))) => definitions
+            .iter()
+            .all(|definition| definition.value.is_none()),
+        _ => false,
+    }
+}
+
+// infers the widths of a parsed program. the returned program defines all of
+// its modules before any other statement, it comes with the warnings about it.
+pub fn resolve(mut program: Program, options: ResolverOptions) -> (Program, Vec<Warning>) {
+    let mut resolver = Resolver::new(options);
+
+    resolver.collect_modules(&mut program.statements);
+
+    // the declarations are resolved first, so a module can read them with
+    // @global wherever they are in the file
+    let (mut statements, rest): (Vec<_>, Vec<_>) = mem::take(&mut program.statements)
+        .into_iter()
+        .partition(is_declaration);
+    statements.extend(rest);
+    program.statements = statements;
+
+    resolver.resolve_statements(&mut program.statements);
+
+    // modules that are never used still have to be checked
+    for name in resolver.order.clone() {
+        resolver.signature(&name);
+    }
+
+    // modules can read globals, so this waits until all of them are resolved
+    resolver.check_usage();
+    resolver.check_loops(&program.statements);
+
+    let mut statements: Vec<_> = resolver
+        .order
+        .iter()
+        .map(|name| resolver.resolved.remove(name).unwrap())
+        .collect();
+    statements.append(&mut program.statements);
+
+    (Program { statements }, resolver.warnings.take())
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use crate::{parser::datatype::KnownBitWidth, types::decorator::PortType};
+
+use super::Resolver;
+
+impl Resolver {
+    // the width of a bundle is an object with one key per field
+    pub fn bundle_width(&mut self, name: &str) -> KnownBitWidth {
+        if let Some(width) = self.bundle_widths.get(name) {
+            return width.clone();
+        }
+
+        if self.resolving_bundles.iter().any(|bundle| bundle == name) {
+            panic!("Bundle {} contains itself", name);
+        }
+
+        let mut bundle = self
+            .bundles
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| panic!("Bundle {} not found", name));
+
+        self.resolving_bundles.push(name.to_string());
+
+        let mut fields = vec![];
+        for field in bundle.fields.iter_mut() {
+            if fields
+                .iter()
+                .any(|(field_name, _)| *field_name == field.name)
+            {
+                panic!(
+                    "Bundle {} has more than one field called {}",
+                    name, field.name
+                );
+            }
+
+            let width = self.resolve_port_type(&mut field.port_type);
+            fields.push((field.name.clone(), Arc::new(width)));
+        }
+
+        self.resolving_bundles.pop();
+
+        let width = KnownBitWidth::Object(fields.into_iter().collect());
+        self.bundle_widths.insert(name.to_string(), width.clone());
+
+        width
+    }
+
+    pub fn resolve_port_type(&mut self, port_type: &mut PortType) -> KnownBitWidth {
+        match port_type {
+            PortType::Bits(0) => self.error("A wire can't be 0 bits wide".to_string()),
+            PortType::Bits(bits) => KnownBitWidth::Fixed(*bits),
+            PortType::Bundle(name, width) => {
+                *width = self.bundle_width(name);
+
+                width.clone()
+            }
+            PortType::Array(element, length) => {
+                let width = Arc::new(self.resolve_port_type(element));
+
+                KnownBitWidth::Array(vec![width; *length as usize])
+            }
+        }
+    }
+}
+
+// checks a value that is passed where a bundle or an array is expected. it
+// needs exactly the fields of the bundle (or elements of the array), and no
+// field can be wider than in the bundle. narrower fields are zero-extended,
+// like any other wire.
+pub fn check_bundle(
+    resolver: &Resolver,
+    expected: &KnownBitWidth,
+    actual: &KnownBitWidth,
+    path: &str,
+) {
+    match (expected, actual) {
+        (KnownBitWidth::Object(expected), KnownBitWidth::Object(actual)) => {
+            let mut keys: Vec<_> = expected.keys().collect();
+            keys.sort();
+
+            for key in keys {
+                let field = actual.get(key).unwrap_or_else(|| {
+                    resolver.error(format!("{} is missing the field {}", path, key))
+                });
+
+                check_bundle(
+                    resolver,
+                    &expected[key],
+                    field,
+                    &format!("{}.{}", path, key),
+                );
+            }
+
+            let mut keys: Vec<_> = actual.keys().collect();
+            keys.sort();
+
+            if let Some(key) = keys.into_iter().find(|key| !expected.contains_key(*key)) {
+                resolver.error(format!(
+                    "{} has a field {} that isn't part of the bundle",
+                    path, key
+                ));
+            }
+        }
+        (KnownBitWidth::Array(expected), KnownBitWidth::Array(actual)) => {
+            if expected.len() != actual.len() {
+                resolver.error(format!(
+                    "{} has {} elements, but {} are expected",
+                    path,
+                    actual.len(),
+                    expected.len()
+                ));
+            }
+
+            for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                check_bundle(resolver, expected, actual, &format!("{}[{}]", path, index));
+            }
+        }
+        (KnownBitWidth::Object(_), _) => resolver.error(format!("{} has to be a bundle", path)),
+        (_, KnownBitWidth::Object(_)) => resolver.error(format!("{} can't be a bundle", path)),
+        (KnownBitWidth::Array(_), _) => resolver.error(format!("{} has to be an array", path)),
+        (_, KnownBitWidth::Array(_)) => resolver.error(format!("{} can't be an array", path)),
+        (expected, actual) => {
+            if actual.get_size() > expected.get_size() {
+                resolver.error(format!(
+                    "{} is {} bits wide, but only {} bits fit",
+                    path,
+                    actual.get_size(),
+                    expected.get_size()
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use std::sync::Arc;
+
+use crate::{
+    parser::datatype::KnownBitWidth,
+    types::expression::{
+        BinaryOp, Combine, Drive, Expression, ExpressionWithWidth, Extract, ExtractInner,
+        ModuleUse, Repeat, UnaryOp,
+    },
+    utils::integer_width::integer_width,
+};
+
+use super::{bundle::check_bundle, Resolve, Resolver, Signature};
+
+impl Resolve for ExpressionWithWidth {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        self.width = self.expression.resolve(resolver);
+
+        self.width.clone()
+    }
+}
+
+impl Resolve for Arc<ExpressionWithWidth> {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        Arc::make_mut(self).resolve(resolver)
+    }
+}
+
+impl Resolve for Expression {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        match self {
+            Expression::Integer(number) => KnownBitWidth::Fixed(integer_width(*number)),
+            Expression::Variable(variable) => resolver.read_variable(variable, None),
+            Expression::UnaryOp(op) => op.resolve(resolver),
+            Expression::BinaryOp(op) => op.resolve(resolver),
+            Expression::Extract(extract) => extract.resolve(resolver),
+            Expression::Combine(combine) => combine.resolve(resolver),
+            Expression::ModuleUse(module_use) => module_use.resolve(resolver),
+            Expression::Drive(drive) => drive.resolve(resolver),
+            Expression::Repeat(repeat) => repeat.resolve(resolver),
+            Expression::String(_) => KnownBitWidth::Fixed(0),
+        }
+    }
+}
+
+impl Resolve for UnaryOp {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        match self {
+            UnaryOp::Not(expr) => expr.resolve(resolver),
+        }
+    }
+}
+
+impl Resolve for BinaryOp {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        match self {
+            BinaryOp::And(lhs, rhs)
+            | BinaryOp::NAnd(lhs, rhs)
+            | BinaryOp::Or(lhs, rhs)
+            | BinaryOp::NOr(lhs, rhs)
+            | BinaryOp::XOr(lhs, rhs)
+            | BinaryOp::XNOr(lhs, rhs) => {
+                let width = KnownBitWidth::max(lhs.resolve(resolver), rhs.resolve(resolver));
+
+                // the narrower operand is zero-extended
+                if let KnownBitWidth::Fixed(width) = width {
+                    check_cast(resolver, lhs, width, "used in an operation");
+                    check_cast(resolver, rhs, width, "used in an operation");
+                }
+
+                width
+            }
+
+            BinaryOp::Multiplex(lhs, rhs) => {
+                lhs.resolve(resolver);
+                rhs.resolve(resolver);
+
+                // the lhs MUST be a Combine
+                if let Expression::Combine(Combine::Bits(lhs)) = &lhs.as_ref().expression {
+                    let max_size = lhs
+                        .iter()
+                        .map(|expr| {
+                            if let KnownBitWidth::Fixed(width) = expr.width {
+                                width
+                            } else {
+                                panic!("Unknown bit width at multiplexer lhs")
+                            }
+                        })
+                        .max()
+                        .unwrap_or_else(|| panic!("Multiplexer lhs is empty"));
+
+                    for value in lhs {
+                        check_cast(resolver, value, max_size, "selected by a multiplexer");
+                    }
+
+                    KnownBitWidth::Fixed(max_size)
+                } else {
+                    panic!("Multiplex lhs must be a Combine");
+                }
+            }
+        }
+    }
+}
+
+impl Resolve for Extract {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        // reading a field of a variable leaves its other fields unused
+        let self_bit_width = match (&self.expression.expression, &self.extract) {
+            (Expression::Variable(variable), ExtractInner::Name(key)) => {
+                let width = resolver.read_variable(variable, Some(key));
+                Arc::make_mut(&mut self.expression).width = width.clone();
+
+                width
+            }
+            _ => self.expression.resolve(resolver),
+        };
+
+        match &self.extract {
+            ExtractInner::Bit(_) => KnownBitWidth::Fixed(1),
+            ExtractInner::Range(start, end) => {
+                if start > end {
+                    panic!("Start index must be less than or equal to end index");
+                }
+
+                KnownBitWidth::Fixed(1 + (end - start))
+            }
+            ExtractInner::Name(key) => {
+                if let KnownBitWidth::Object(map) = self_bit_width {
+                    map.get(key.as_str())
+                        .unwrap_or_else(|| panic!("Key {} not found in object", key))
+                        .as_ref()
+                        .clone()
+                } else {
+                    panic!("Extracting key {} from non-object", key);
+                }
+            }
+            ExtractInner::Element(index) => {
+                if let KnownBitWidth::Array(elements) = self_bit_width {
+                    elements
+                        .get(*index as usize)
+                        .unwrap_or_else(|| {
+                            panic!(
+                                "Index {} is out of range, the array has {} elements",
+                                index,
+                                elements.len()
+                            )
+                        })
+                        .as_ref()
+                        .clone()
+                } else {
+                    panic!("Indexing a non-array with {}", index);
+                }
+            }
+        }
+    }
+}
+
+impl Resolve for Combine {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        match self {
+            Combine::Bits(bits) => {
+                for bit in bits.iter_mut() {
+                    bit.resolve(resolver);
+                }
+
+                KnownBitWidth::Fixed(bits.len() as u32)
+            }
+            Combine::Obj(values) => KnownBitWidth::Object(
+                values
+                    .iter_mut()
+                    .map(|(key, value)| (key.clone(), Arc::new(value.resolve(resolver))))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// names every positional argument after the input at its position, inputs
+// are counted in the order they are declared. positional arguments come
+// first, every port can be connected once.
+fn bind_arguments(resolver: &Resolver, module_use: &mut ModuleUse, signature: &Signature) {
+    let mut named = false;
+
+    for (position, argument) in module_use.arguments.iter_mut().enumerate() {
+        if argument.name.is_some() {
+            named = true;
+            continue;
+        }
+
+        if named {
+            resolver.error(format!(
+                "Positional argument {} of module {} comes after a named argument",
+                position + 1,
+                module_use.name
+            ));
+        }
+
+        let input = signature.inputs.get(position).unwrap_or_else(|| {
+            resolver.error(format!(
+                "Module {} has {} inputs, but more positional arguments are given",
+                module_use.name,
+                signature.inputs.len()
+            ))
+        });
+
+        argument.name = Some(input.name.clone());
+    }
+
+    let mut connected: Vec<&str> = vec![];
+
+    for argument in &module_use.arguments {
+        let name = argument.name.as_deref().unwrap();
+
+        if !signature
+            .inputs
+            .iter()
+            .chain(&signature.inouts)
+            .any(|port| port.name == name)
+        {
+            resolver.error(format!(
+                "Module {} has no input called {}",
+                module_use.name, name
+            ));
+        }
+
+        if connected.contains(&name) {
+            resolver.error(format!(
+                "Input {} of module {} is connected more than once",
+                name, module_use.name
+            ));
+        }
+
+        connected.push(name);
+    }
+}
+
+// a constant, possibly driven onto a bus, is never reported for being
+// extended
+pub fn is_constant(value: &ExpressionWithWidth) -> bool {
+    match &value.expression {
+        Expression::Integer(_) => true,
+        Expression::Drive(Drive { value, .. }) => is_constant(value),
+        _ => false,
+    }
+}
+
+// reports a value that is implicitly cast to `to` bits. cutting off bits is
+// always reported. extending a value that isn't a constant only with
+// --warn-widening, or in strict mode, where every warning is an error.
+pub fn check_cast(resolver: &Resolver, value: &ExpressionWithWidth, to: u32, connection: &str) {
+    let KnownBitWidth::Fixed(from) = value.width else {
+        return;
+    };
+
+    if from > to {
+        resolver.warn(
+            "width",
+            format!(
+                "{} bits are {}, which is {} bits wide, the upper bits are cut off",
+                from, connection, to
+            ),
+        );
+    } else if from < to
+        && (resolver.options.strict_widths || resolver.options.warn_widening)
+        && !is_constant(value)
+    {
+        resolver.warn(
+            "width",
+            format!(
+                "{} bits are {}, which is {} bits wide, they are zero-extended",
+                from, connection, to
+            ),
+        );
+    }
+}
+
+impl Resolve for ModuleUse {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        for argument in self.arguments.iter_mut() {
+            argument.value.resolve(resolver);
+        }
+
+        let signature = resolver.signature(&self.name);
+
+        bind_arguments(resolver, self, &signature);
+
+        // bundles and arrays are checked at module boundaries, plain wires
+        // are cast
+        for input in &signature.inputs {
+            let Some(argument) = self.argument(&input.name) else {
+                if input.default.is_none() {
+                    resolver.error(format!(
+                        "Input {} of module {} is not connected and has no default value",
+                        input.name, self.name
+                    ));
+                }
+
+                continue;
+            };
+
+            if input.width.is_structured() {
+                check_bundle(
+                    resolver,
+                    &input.width,
+                    &argument.value.width,
+                    &format!("{}.{}", self.name, input.name),
+                );
+                continue;
+            }
+
+            check_cast(
+                resolver,
+                &argument.value,
+                input.width.get_size(),
+                &format!("connected to input {} of module {}", input.name, self.name),
+            );
+        }
+
+        // a bidirectional port is the wire it is connected to, it can't be
+        // cast
+        for inout in &signature.inouts {
+            let Some(argument) = self.argument(&inout.name) else {
+                continue;
+            };
+
+            let (from, to) = (argument.value.width.get_size(), inout.width.get_size());
+
+            // the widths of inferred wires are only guesses while sizing
+            if from != to && !resolver.sizing() {
+                resolver.error(format!(
+                    "{} bits are connected to inout {} of module {}, which is {} bits wide",
+                    from, inout.name, self.name, to
+                ));
+            }
+        }
+
+        signature.outputs
+    }
+}
+
+impl Resolve for Drive {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        let enable_width = self.enable.resolve(resolver);
+
+        if enable_width.get_size() != 1 {
+            panic!(
+                "The enable of drive() has to be 1 bit wide, not {}",
+                enable_width.get_size()
+            );
+        }
+
+        self.value.resolve(resolver)
+    }
+}
+
+impl Resolve for Repeat {
+    fn resolve(&mut self, resolver: &mut Resolver) -> KnownBitWidth {
+        let width = Arc::new(self.value.resolve(resolver));
+
+        KnownBitWidth::Array(vec![width; self.count as usize])
+    }
+}
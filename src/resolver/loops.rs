@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use crate::{
+    parser::datatype::KnownBitWidth,
+    types::{
+        decorator::Decorator,
+        expression::{
+            BinaryOp, Combine, Drive, Expression, Extract, ExtractInner, Repeat, UnaryOp,
+        },
+        program::ProgramStatement,
+        source_info::SourceInfo,
+    },
+    utils::indexed_name::{indexed_name, split_indexed_name},
+};
+
+use super::Resolver;
+
+// a signal and the signals its value is computed from. external modules are
+// assumed to store their outputs, nothing is computed through them.
+struct Node {
+    dependencies: Vec<String>,
+    source: SourceInfo,
+    // any statement that drives the signal can allow the loop
+    allowed: bool,
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: HashMap<String, Node>,
+    // in the order they are defined, loops are reported in that order
+    order: Vec<String>,
+}
+
+// `x.sum` is part of `x`, and `x[3]` is part of the array `x`
+fn parent(name: &str) -> Option<&str> {
+    if let Some((array, _)) = split_indexed_name(name) {
+        return Some(array);
+    }
+
+    name.rfind('.').map(|dot| &name[..dot])
+}
+
+// `sum.low` is the field `low` of the output `sum`
+fn split_field(field: Option<&str>) -> (Option<&str>, Option<&str>) {
+    match field.map(|field| field.split_once('.')) {
+        Some(Some((first, rest))) => (Some(first), Some(rest)),
+        Some(None) => (field, None),
+        None => (None, None),
+    }
+}
+
+fn path(name: &str, field: Option<&str>) -> String {
+    match field {
+        Some(field) => format!("{}.{}", name, field),
+        None => name.to_string(),
+    }
+}
+
+impl Graph {
+    fn add(&mut self, name: String, dependencies: Vec<String>, source: &SourceInfo) {
+        if let Some(node) = self.nodes.get_mut(&name) {
+            node.dependencies.extend(dependencies);
+            node.allowed |= source.allows("loop");
+            return;
+        }
+
+        self.order.push(name.clone());
+        self.nodes.insert(
+            name,
+            Node {
+                dependencies,
+                source: source.clone(),
+                allowed: source.allows("loop"),
+            },
+        );
+    }
+
+    // the nodes a name that is read stands for. `x.sum` can be all of `x`,
+    // and `x` can be the array of `x[0]`, `x[1]`, ...
+    fn lookup(&self, name: &str) -> Vec<String> {
+        if self.nodes.contains_key(name) {
+            return vec![name.to_string()];
+        }
+
+        if let Some(parent) = parent(name) {
+            return self.lookup(parent);
+        }
+
+        self.order
+            .iter()
+            .filter(|node| split_indexed_name(node).is_some_and(|(array, _)| array == name))
+            .cloned()
+            .collect()
+    }
+
+    fn edges(&self, name: &str) -> Vec<String> {
+        self.nodes[name]
+            .dependencies
+            .iter()
+            .flat_map(|dependency| self.lookup(dependency))
+            .collect()
+    }
+
+    // the strongly connected components with a loop in them, tarjan's
+    // algorithm
+    fn loops(&self) -> Vec<Vec<String>> {
+        struct State {
+            index: HashMap<String, usize>,
+            low: HashMap<String, usize>,
+            stack: Vec<String>,
+            loops: Vec<Vec<String>>,
+        }
+
+        fn visit(graph: &Graph, name: &str, state: &mut State) {
+            let index = state.index.len();
+            state.index.insert(name.to_string(), index);
+            state.low.insert(name.to_string(), index);
+            state.stack.push(name.to_string());
+
+            let edges = graph.edges(name);
+
+            for next in &edges {
+                if !state.index.contains_key(next) {
+                    visit(graph, next, state);
+                    state
+                        .low
+                        .insert(name.to_string(), state.low[name].min(state.low[next]));
+                } else if state.stack.contains(next) {
+                    state
+                        .low
+                        .insert(name.to_string(), state.low[name].min(state.index[next]));
+                }
+            }
+
+            if state.low[name] != index {
+                return;
+            }
+
+            let position = state.stack.iter().rposition(|node| node == name).unwrap();
+            let component = state.stack.split_off(position);
+
+            if component.len() > 1 || edges.iter().any(|next| next == name) {
+                state.loops.push(component);
+            }
+        }
+
+        let mut state = State {
+            index: HashMap::new(),
+            low: HashMap::new(),
+            stack: vec![],
+            loops: vec![],
+        };
+
+        for name in &self.order {
+            if !state.index.contains_key(name) {
+                visit(self, name, &mut state);
+            }
+        }
+
+        state.loops
+    }
+
+    // the inputs that `name` is computed from
+    fn inputs(&self, name: &str, inputs: &[String]) -> Vec<String> {
+        let mut seen = vec![];
+        let mut queue = vec![name.to_string()];
+
+        while let Some(name) = queue.pop() {
+            if seen.contains(&name) {
+                continue;
+            }
+
+            queue.extend(self.edges(&name));
+            seen.push(name);
+        }
+
+        inputs
+            .iter()
+            .filter(|input| seen.contains(input))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Resolver {
+    // the signals that `field` of a value is computed from, without going
+    // through storage
+    fn dependencies(&self, expression: &Expression, field: Option<&str>) -> Vec<String> {
+        match expression {
+            Expression::Integer(_) | Expression::String(_) => vec![],
+            Expression::Variable(name) => vec![path(name, field)],
+            Expression::Extract(Extract {
+                expression,
+                extract,
+            }) => match extract {
+                ExtractInner::Name(key) => {
+                    self.dependencies(&expression.expression, Some(&path(key, field)))
+                }
+                ExtractInner::Element(index) => match &expression.expression {
+                    Expression::Variable(name) => vec![path(&indexed_name(name, *index), field)],
+                    expression => self.dependencies(expression, None),
+                },
+                ExtractInner::Bit(_) | ExtractInner::Range(..) => {
+                    self.dependencies(&expression.expression, None)
+                }
+            },
+            Expression::UnaryOp(UnaryOp::Not(value)) => self.dependencies(&value.expression, None),
+            Expression::BinaryOp(
+                BinaryOp::And(lhs, rhs)
+                | BinaryOp::NAnd(lhs, rhs)
+                | BinaryOp::Or(lhs, rhs)
+                | BinaryOp::NOr(lhs, rhs)
+                | BinaryOp::XOr(lhs, rhs)
+                | BinaryOp::XNOr(lhs, rhs)
+                | BinaryOp::Multiplex(lhs, rhs),
+            ) => [
+                self.dependencies(&lhs.expression, None),
+                self.dependencies(&rhs.expression, None),
+            ]
+            .concat(),
+            Expression::Combine(Combine::Bits(bits)) => bits
+                .iter()
+                .flat_map(|bit| self.dependencies(&bit.expression, None))
+                .collect(),
+            Expression::Combine(Combine::Obj(values)) => {
+                let (key, rest) = split_field(field);
+
+                values
+                    .iter()
+                    .filter(|(name, _)| key.is_none_or(|key| key == name.as_str()))
+                    .flat_map(|(_, value)| self.dependencies(value, rest))
+                    .collect()
+            }
+            Expression::ModuleUse(module_use) => {
+                let (output, _) = split_field(field);
+
+                self.signatures[&module_use.name]
+                    .paths
+                    .iter()
+                    .filter(|(name, _)| output.is_none_or(|output| output == name.as_str()))
+                    .flat_map(|(_, inputs)| inputs)
+                    .filter_map(|input| module_use.argument(input))
+                    .flat_map(|argument| self.dependencies(&argument.value.expression, None))
+                    .collect()
+            }
+            Expression::Drive(Drive { value, enable }) => [
+                self.dependencies(&value.expression, None),
+                self.dependencies(&enable.expression, None),
+            ]
+            .concat(),
+            Expression::Repeat(Repeat { value, .. }) => self.dependencies(&value.expression, None),
+        }
+    }
+
+    // warns about every loop that doesn't go through storage, unless one of
+    // its signals allows it. returns the inputs every output is computed
+    // from, for the signature of a module.
+    pub fn check_loops(&self, statements: &[ProgramStatement]) -> HashMap<String, Vec<String>> {
+        let mut graph = Graph::default();
+        let mut inputs = vec![];
+        let mut outputs = vec![];
+
+        for statement in statements {
+            let ProgramStatement::VariableDefinitions(definitions) = statement else {
+                continue;
+            };
+
+            let source = &definitions.source;
+
+            for definition in &definitions.definitions {
+                let name = definition.name.clone();
+
+                match &definitions.decorator {
+                    Some(Decorator::In(..)) => inputs.push(name.clone()),
+                    Some(Decorator::Out(..)) => outputs.push(name.clone()),
+                    _ => {}
+                }
+
+                let Some(value) = &definition.value else {
+                    graph.add(name, vec![], source);
+                    continue;
+                };
+
+                // the default of an input doesn't depend on anything
+                if matches!(definitions.decorator, Some(Decorator::In(..))) {
+                    graph.add(name, vec![], source);
+                    continue;
+                }
+
+                let KnownBitWidth::Object(fields) = &value.width else {
+                    graph.add(name, self.dependencies(&value.expression, None), source);
+                    continue;
+                };
+
+                let mut keys: Vec<_> = fields.keys().collect();
+                keys.sort();
+
+                for key in &keys {
+                    let dependencies = self.dependencies(&value.expression, Some(key));
+                    graph.add(path(&name, Some(key)), dependencies, source);
+                }
+
+                let fields = keys.iter().map(|key| path(&name, Some(key))).collect();
+                graph.add(name, fields, source);
+            }
+        }
+
+        for signals in graph.loops() {
+            let signals: Vec<_> = graph
+                .order
+                .iter()
+                .filter(|name| signals.contains(name))
+                .collect();
+
+            if signals.iter().any(|name| graph.nodes[*name].allowed) {
+                continue;
+            }
+
+            let names: Vec<_> = signals.iter().map(|name| name.as_str()).collect();
+
+            self.warn_at(
+                &graph.nodes[signals[0]].source,
+                "loop",
+                format!("Combinational loop through {}", names.join(", ")),
+            );
+        }
+
+        outputs
+            .into_iter()
+            .map(|output| {
+                let inputs = graph.inputs(&output, &inputs);
+                (output, inputs)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,136 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    parser::{datatype::KnownBitWidth, ParserModuleVariableData},
+    types::{
+        decorator::Decorator,
+        expression::Expression,
+        module::{ExternalModule, ExternalModuleVariableData, Module},
+        program::ProgramStatement,
+    },
+};
+
+use super::{Resolver, Signature};
+
+impl Resolver {
+    pub fn resolve_module(&mut self, mut module: Module) {
+        self.resolving.push(module.name.clone());
+        self.scopes.push(vec![]);
+
+        self.resolve_statements(&mut module.statements);
+        self.check_usage();
+        let paths = self.check_loops(&module.statements);
+
+        self.scopes.pop();
+        self.resolving.pop();
+
+        let mut inputs = vec![];
+        let mut outputs = vec![];
+        let mut inouts = vec![];
+
+        for statement in &module.statements {
+            let ProgramStatement::VariableDefinitions(definitions) = statement else {
+                continue;
+            };
+
+            for definition in &definitions.definitions {
+                let data = |width: KnownBitWidth| ParserModuleVariableData {
+                    name: definition.name.clone(),
+                    width,
+                    default: None,
+                };
+
+                match &definitions.decorator {
+                    Some(Decorator::In(port_type, _)) => {
+                        // defaults have been checked to be constants
+                        let default = match definition.value.as_ref().map(|v| &v.expression) {
+                            Some(Expression::Integer(default)) => Some(*default),
+                            _ => None,
+                        };
+
+                        inputs.push(ParserModuleVariableData {
+                            default,
+                            ..data(port_type.width())
+                        })
+                    }
+                    Some(Decorator::InOut(width, _)) => {
+                        inouts.push(data(KnownBitWidth::Fixed(*width)))
+                    }
+                    Some(Decorator::Clock(_)) => inputs.push(data(KnownBitWidth::Fixed(1))),
+                    Some(Decorator::Out(port_type, _)) => {
+                        let width = match port_type {
+                            Some(port_type) => port_type.width(),
+                            None => definition.value.as_ref().unwrap().width.clone(),
+                        };
+
+                        outputs.push(data(width))
+                    }
+                    Some(Decorator::Wire(_)) | Some(Decorator::Global) | None => {}
+                }
+            }
+        }
+
+        // bidirectional ports can be read from the outside as well
+        let readable: Vec<_> = outputs.iter().chain(&inouts).cloned().collect();
+        self.signatures.insert(
+            module.name.clone(),
+            Signature {
+                inputs: inputs.clone(),
+                inouts: inouts.clone(),
+                outputs: object_width(&readable),
+                output_names: readable.iter().map(|output| output.name.clone()).collect(),
+                paths,
+            },
+        );
+
+        module.inputs = inputs;
+        module.outputs = outputs;
+        module.inouts = inouts;
+
+        self.resolved
+            .insert(module.name.clone(), ProgramStatement::Module(module));
+    }
+
+    pub fn resolve_external_module(&mut self, module: ExternalModule) {
+        let data = |variable: &ExternalModuleVariableData| ParserModuleVariableData {
+            name: variable.name.clone(),
+            width: variable.width.clone(),
+            default: variable.default,
+        };
+
+        let inputs = module.inputs.iter().map(data).collect();
+        let inouts = module.inouts.iter().map(data).collect();
+        let outputs: Vec<_> = module
+            .outputs
+            .iter()
+            .chain(&module.inouts)
+            .map(data)
+            .collect();
+
+        self.signatures.insert(
+            module.name.clone(),
+            Signature {
+                inputs,
+                inouts,
+                outputs: object_width(&outputs),
+                output_names: outputs.iter().map(|output| output.name.clone()).collect(),
+                paths: HashMap::new(),
+            },
+        );
+
+        self.resolved.insert(
+            module.name.clone(),
+            ProgramStatement::ExternalModule(module),
+        );
+    }
+}
+
+// a module use returns an object with one key per output
+fn object_width(outputs: &[ParserModuleVariableData]) -> KnownBitWidth {
+    KnownBitWidth::Object(
+        outputs
+            .iter()
+            .map(|output| (output.name.clone(), Arc::new(output.width.clone())))
+            .collect(),
+    )
+}
@@ -0,0 +1,195 @@
+use std::{collections::HashMap, mem};
+
+use crate::{
+    parser::datatype::KnownBitWidth,
+    types::{
+        decorator::{Decorator, PortType},
+        expression::ExpressionWithWidth,
+        program::ProgramStatement,
+        variable_definition::VariableDefinitions,
+    },
+};
+
+use super::{expression::is_constant, Inference, Resolve, Resolver};
+
+// the wires declared with `@wire` but without a width
+fn inferred_wires(statements: &[ProgramStatement]) -> Vec<String> {
+    statements
+        .iter()
+        .filter_map(|statement| match statement {
+            ProgramStatement::VariableDefinitions(VariableDefinitions {
+                definitions,
+                decorator: Some(Decorator::Wire(None)),
+                ..
+            }) => Some(definitions.iter().map(|definition| definition.name.clone())),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+impl Resolver {
+    // resolves statements in order, destructuring is replaced by the plain
+    // definitions it stands for on the way.
+    //
+    // a wire without a width is as wide as the values assigned to it. the
+    // wire can be used before it is assigned, so the statements are resolved
+    // with a guess for every such width first, starting at 1 bit, until
+    // the widths don't change anymore.
+    pub fn resolve_statements(&mut self, statements: &mut Vec<ProgramStatement>) {
+        let inferred = inferred_wires(statements);
+
+        self.inference.push(Inference::default());
+
+        if !inferred.is_empty() {
+            self.infer_widths(statements, &inferred);
+        }
+
+        self.resolve_in_order(statements);
+
+        self.inference.pop();
+    }
+
+    fn infer_widths(&mut self, statements: &[ProgramStatement], inferred: &[String]) {
+        loop {
+            let scope = self.scopes.last().unwrap().clone();
+
+            let inference = self.inference.last_mut().unwrap();
+            inference.sizing = true;
+            inference.assignments.clear();
+
+            self.resolve_in_order(&mut statements.to_vec());
+
+            let resolved = mem::replace(self.scopes.last_mut().unwrap(), scope);
+
+            // a module that was resolved on the way isn't resolved again, the
+            // globals it reads stay read
+            for variable in self.scopes.last_mut().unwrap() {
+                if let Some(resolved) = resolved.iter().find(|v| v.name == variable.name) {
+                    variable.used = resolved.used.clone();
+                }
+            }
+
+            self.inference.last_mut().unwrap().sizing = false;
+
+            let inference = self.inference.last().unwrap();
+            let mut widths = HashMap::new();
+
+            for (name, width, _, source) in &inference.assignments {
+                if !inferred.contains(name) {
+                    continue;
+                }
+
+                let KnownBitWidth::Fixed(width) = width else {
+                    self.error_at(
+                        source,
+                        format!(
+                            "The width of {} can only be inferred from plain values, give it a type",
+                            name
+                        ),
+                    );
+                };
+
+                let inferred = widths.entry(name.clone()).or_insert(*width);
+                *inferred = (*inferred).max(*width);
+            }
+
+            if widths == inference.widths {
+                break;
+            }
+
+            self.inference.last_mut().unwrap().widths = widths;
+        }
+
+        // constants are extended to the width of the wire
+        let inference = self.inference.last().unwrap();
+
+        for (name, width, constant, source) in &inference.assignments {
+            let Some(inferred) = inference.widths.get(name) else {
+                continue;
+            };
+
+            if !constant && width.get_size() != *inferred {
+                self.error_at(
+                    source,
+                    format!(
+                        "{} is assigned values of different widths, {} and {} bits, give it a width",
+                        name,
+                        width.get_size(),
+                        inferred
+                    ),
+                );
+            }
+        }
+    }
+
+    // remembers the width of a value assigned to a declared wire
+    pub fn record_assignment(&mut self, name: &str, value: &ExpressionWithWidth) {
+        if let Some(inference) = self.inference.last_mut() {
+            inference.assignments.push((
+                name.to_string(),
+                value.width.clone(),
+                is_constant(value),
+                self.source.clone(),
+            ));
+        }
+    }
+
+    fn resolve_in_order(&mut self, statements: &mut Vec<ProgramStatement>) {
+        for statement in mem::take(statements) {
+            match statement {
+                ProgramStatement::Destructuring(destructuring) => {
+                    let source = mem::replace(&mut self.source, destructuring.source.clone());
+                    statements.extend(self.resolve_destructuring(destructuring));
+                    self.source = source;
+                }
+                // every wire gets a definition of its own with the width that
+                // was inferred for it, 1 bit if it is never assigned
+                ProgramStatement::VariableDefinitions(VariableDefinitions {
+                    definitions,
+                    decorator: Some(Decorator::Wire(None)),
+                    source,
+                }) => {
+                    for definition in definitions {
+                        let width = self.inference.last().unwrap().widths.get(&definition.name);
+
+                        let mut statement =
+                            ProgramStatement::VariableDefinitions(VariableDefinitions {
+                                decorator: Some(Decorator::Wire(Some(PortType::Bits(
+                                    width.copied().unwrap_or(1),
+                                )))),
+                                definitions: vec![definition],
+                                source: source.clone(),
+                            });
+
+                        self.resolve_statement(&mut statement);
+                        statements.push(statement);
+                    }
+                }
+                mut statement => {
+                    self.resolve_statement(&mut statement);
+                    statements.push(statement);
+                }
+            }
+        }
+    }
+
+    pub fn resolve_statement(&mut self, statement: &mut ProgramStatement) {
+        match statement {
+            ProgramStatement::VariableDefinitions(definitions) => {
+                let source = mem::replace(&mut self.source, definitions.source.clone());
+                self.resolve_definitions(definitions);
+                self.source = source;
+            }
+            ProgramStatement::Expression(expr) => {
+                expr.resolve(self);
+            }
+            // modules and bundles are collected before anything is resolved,
+            // destructuring is handled by resolve_statements
+            ProgramStatement::Destructuring(_)
+            | ProgramStatement::Module(_)
+            | ProgramStatement::ExternalModule(_)
+            | ProgramStatement::Bundle(_) => unreachable!(),
+        }
+    }
+}
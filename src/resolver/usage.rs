@@ -0,0 +1,98 @@
+use crate::{
+    parser::datatype::KnownBitWidth,
+    utils::indexed_name::{indexed_name, split_indexed_name},
+};
+
+use super::{Resolver, Used, Variable};
+
+// the parts of a declared wire that nothing drives, an array can be driven
+// element by element
+fn undriven(scope: &[Variable], name: &str, width: &KnownBitWidth) -> Vec<String> {
+    let driven = scope.iter().any(|variable| {
+        variable.name == name && variable.drivers.as_ref().is_some_and(|d| !d.is_empty())
+    });
+
+    match width {
+        _ if driven => vec![],
+        KnownBitWidth::Array(elements) => elements
+            .iter()
+            .enumerate()
+            .flat_map(|(index, width)| undriven(scope, &indexed_name(name, index as u32), width))
+            .collect(),
+        _ => vec![name.to_string()],
+    }
+}
+
+// if any element of an array is read on its own
+fn element_used(scope: &[Variable], name: &str) -> bool {
+    scope.iter().any(|variable| {
+        split_indexed_name(&variable.name).is_some_and(|(array, _)| array == name)
+            && (variable.used != Used::Nothing || element_used(scope, &variable.name))
+    })
+}
+
+impl Resolver {
+    // warns about the variables of the innermost scope that are never read,
+    // and about declared wires that are never driven
+    pub fn check_usage(&self) {
+        let scope = self.scopes.last().unwrap();
+
+        for variable in scope {
+            // destructuring leaves out the outputs it doesn't need
+            if variable.port || variable.name.starts_with('(') {
+                continue;
+            }
+
+            // the elements of a declared array are checked with the array
+            let declared_element = split_indexed_name(&variable.name).is_some_and(|(array, _)| {
+                scope
+                    .iter()
+                    .any(|other| other.name == array && other.drivers.is_some())
+            });
+
+            if declared_element {
+                continue;
+            }
+
+            if variable.drivers.is_some() {
+                for name in undriven(scope, &variable.name, &variable.width) {
+                    self.warn_at(
+                        &variable.source,
+                        "undriven",
+                        format!("{} is declared, but nothing drives it", name),
+                    );
+                }
+            }
+
+            match &variable.used {
+                Used::All => {}
+                Used::Nothing => {
+                    if !element_used(scope, &variable.name) {
+                        self.warn_at(
+                            &variable.source,
+                            "unused",
+                            format!("{} is never used", variable.name),
+                        );
+                    }
+                }
+                Used::Fields(fields) => {
+                    let KnownBitWidth::Object(all) = &variable.width else {
+                        continue;
+                    };
+
+                    let mut unused: Vec<_> =
+                        all.keys().filter(|key| !fields.contains(key)).collect();
+                    unused.sort();
+
+                    for field in unused {
+                        self.warn_at(
+                            &variable.source,
+                            "unused",
+                            format!("{}.{} is never used", variable.name, field),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
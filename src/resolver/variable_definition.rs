@@ -0,0 +1,305 @@
+use std::sync::Arc;
+
+use crate::{
+    parser::datatype::KnownBitWidth,
+    types::{
+        decorator::{Decorator, PortType},
+        expression::{Drive, Expression, ExpressionWithWidth, Extract, ExtractInner},
+        program::ProgramStatement,
+        variable_definition::{Destructuring, VariableDefinition, VariableDefinitions},
+    },
+    utils::integer_width::integer_width,
+};
+
+use super::{bundle::check_bundle, expression::check_cast, Resolve, Resolver, WireDriver};
+
+impl Resolver {
+    pub fn resolve_definitions(&mut self, definitions: &mut VariableDefinitions) {
+        let port_width = match &mut definitions.decorator {
+            Some(Decorator::In(port_type, _))
+            | Some(Decorator::Out(Some(port_type), _))
+            | Some(Decorator::Wire(Some(port_type))) => Some(self.resolve_port_type(port_type)),
+            _ => None,
+        };
+
+        for definition in definitions.definitions.iter_mut() {
+            let value_width = definition.value.as_mut().map(|value| value.resolve(self));
+
+            let width = match &definitions.decorator {
+                Some(Decorator::In(port_type, _)) => {
+                    if let Some(value) = &definition.value {
+                        check_default(self, &definition.name, port_type, &value.expression);
+                    }
+
+                    port_width.clone().unwrap()
+                }
+                Some(Decorator::InOut(width, _)) => {
+                    self.add_declared_wire(
+                        definition.name.clone(),
+                        KnownBitWidth::Fixed(*width),
+                        true,
+                    );
+                    continue;
+                }
+                Some(Decorator::Wire(Some(_))) => {
+                    self.add_declared_wire(
+                        definition.name.clone(),
+                        port_width.clone().unwrap(),
+                        false,
+                    );
+                    continue;
+                }
+                // resolve_statements gives every wire its inferred width
+                Some(Decorator::Wire(None)) => unreachable!(),
+                Some(Decorator::Out(port_type, _)) => match port_type {
+                    Some(PortType::Bundle(..)) | Some(PortType::Array(..)) => {
+                        let width = port_width.clone().unwrap();
+
+                        if let Some(value_width) = &value_width {
+                            check_bundle(self, &width, value_width, &definition.name);
+                        }
+
+                        width
+                    }
+                    Some(PortType::Bits(bits)) => {
+                        if let Some(value) = &definition.value {
+                            check_cast(
+                                self,
+                                value,
+                                *bits,
+                                &format!("assigned to output {}", definition.name),
+                            );
+                        }
+
+                        port_width.clone().unwrap()
+                    }
+                    None => value_width.unwrap_or_else(|| {
+                        panic!("Output variable {} has no value", definition.name)
+                    }),
+                },
+                Some(Decorator::Clock(_)) => KnownBitWidth::Fixed(1),
+                Some(Decorator::Global) => {
+                    if definition.value.is_some() {
+                        panic!("@global {} can't have a value", definition.name);
+                    }
+
+                    self.find_global(&definition.name)
+                }
+                None => {
+                    // the parser makes sure plain definitions have a value
+                    let driver = match &definition.value.as_ref().unwrap().expression {
+                        Expression::Drive(Drive { enable, .. }) => match enable.expression {
+                            Expression::Integer(enable) => WireDriver::TriState(Some(enable)),
+                            _ => WireDriver::TriState(None),
+                        },
+                        _ => WireDriver::Plain,
+                    };
+
+                    let value = definition.value.as_ref().unwrap();
+
+                    // a typed value is converted to its type, but it can't
+                    // lose any bits on the way. extending a plain value is
+                    // reported like for any other wire.
+                    let declared = definition.port_type.as_mut().map(|port_type| {
+                        let width = self.resolve_port_type(port_type);
+                        check_bundle(self, &width, &value.width, &definition.name);
+
+                        if let KnownBitWidth::Fixed(bits) = width {
+                            check_cast(
+                                self,
+                                value,
+                                bits,
+                                &format!("assigned to wire {}", definition.name),
+                            );
+                        }
+
+                        width
+                    });
+
+                    if self.add_driver(&definition.name, driver) {
+                        self.record_assignment(&definition.name, value);
+
+                        if let (None, Some(KnownBitWidth::Fixed(bits))) =
+                            (&declared, self.find_variable(&definition.name))
+                        {
+                            check_cast(
+                                self,
+                                value,
+                                bits,
+                                &format!("assigned to wire {}", definition.name),
+                            );
+                        }
+
+                        continue;
+                    }
+
+                    declared.unwrap_or_else(|| value_width.unwrap())
+                }
+            };
+
+            let port = matches!(
+                definitions.decorator,
+                Some(Decorator::In(..)) | Some(Decorator::Out(..)) | Some(Decorator::Clock(_))
+            );
+
+            self.add_variable(definition.name.clone(), width, port);
+
+            // an instance that puts values on a bus does something even if
+            // nothing reads it
+            if definition
+                .value
+                .as_ref()
+                .is_some_and(|value| self.drives_bus(value))
+            {
+                self.mark_used(&definition.name, None);
+            }
+        }
+    }
+
+    // a module use that is connected to a bus through an inout, or that gets
+    // a tri-state value through drive()
+    fn drives_bus(&mut self, value: &ExpressionWithWidth) -> bool {
+        let Expression::ModuleUse(module_use) = &value.expression else {
+            return false;
+        };
+
+        let signature = self.signature(&module_use.name);
+
+        module_use.arguments.iter().any(|argument| {
+            matches!(argument.value.expression, Expression::Drive(_))
+                || signature
+                    .inouts
+                    .iter()
+                    .any(|inout| argument.name.as_ref() == Some(&inout.name))
+        })
+    }
+}
+
+impl Resolver {
+    // `(s, c) = half_adder(...)` becomes `(s, c) = half_adder(...)` followed
+    // by `s = (s, c).sum` and `c = (s, c).carry`. `(s, c)` can't clash with
+    // any other name.
+    pub fn resolve_destructuring(
+        &mut self,
+        mut destructuring: Destructuring,
+    ) -> Vec<ProgramStatement> {
+        let names: Vec<_> = destructuring
+            .fields
+            .iter()
+            .map(|field| field.name.clone())
+            .collect();
+        let temporary = format!("({})", names.join(", "));
+
+        let mut value = VariableDefinitions {
+            definitions: vec![VariableDefinition {
+                name: temporary.clone(),
+                port_type: None,
+                value: Some(destructuring.value.clone()),
+            }],
+            decorator: None,
+            source: destructuring.source.clone(),
+        };
+        self.resolve_definitions(&mut value);
+
+        let width = value.definitions[0].value.as_ref().unwrap().width.clone();
+        let KnownBitWidth::Object(outputs) = width else {
+            self.error(format!(
+                "Only objects can be destructured, {} is not one",
+                temporary
+            ));
+        };
+
+        // what the value is called in errors
+        let source = match &destructuring.value.expression {
+            Expression::ModuleUse(module_use) => Some(module_use.name.clone()),
+            _ => None,
+        };
+
+        if destructuring
+            .fields
+            .iter()
+            .any(|field| field.output.is_none())
+        {
+            let Some(module) = &source else {
+                self.error(format!(
+                    "Only the result of a module can be destructured by position, {} is not one",
+                    temporary
+                ));
+            };
+
+            let output_names = self.signature(module).output_names;
+
+            if output_names.len() != destructuring.fields.len() {
+                self.error(format!(
+                    "Module {} has {} outputs, but {} names are given",
+                    module,
+                    output_names.len(),
+                    destructuring.fields.len()
+                ));
+            }
+
+            for (field, output) in destructuring.fields.iter_mut().zip(output_names) {
+                field.output = Some(output);
+            }
+        }
+
+        let mut fields = VariableDefinitions {
+            definitions: vec![],
+            decorator: destructuring.decorator,
+            source: destructuring.source,
+        };
+
+        for field in destructuring.fields {
+            let output = field.output.unwrap();
+
+            if !outputs.contains_key(&output) {
+                self.error(match &source {
+                    Some(module) => format!("Module {} has no output called {}", module, output),
+                    None => format!("{} has no field called {}", temporary, output),
+                });
+            }
+
+            fields.definitions.push(VariableDefinition {
+                name: field.name,
+                port_type: None,
+                value: Some(ExpressionWithWidth::new(Expression::Extract(Extract {
+                    expression: Arc::new(ExpressionWithWidth::new(Expression::Variable(
+                        temporary.clone(),
+                    ))),
+                    extract: ExtractInner::Name(output),
+                }))),
+            });
+        }
+
+        self.resolve_definitions(&mut fields);
+
+        vec![
+            ProgramStatement::VariableDefinitions(value),
+            ProgramStatement::VariableDefinitions(fields),
+        ]
+    }
+}
+
+// the default of an input is a constant that fits into the input
+fn check_default(resolver: &Resolver, name: &str, port_type: &PortType, default: &Expression) {
+    let PortType::Bits(bits) = port_type else {
+        resolver.error(format!(
+            "Input {} can't have a default value, only plain wires can",
+            name
+        ));
+    };
+
+    let Expression::Integer(default) = default else {
+        resolver.error(format!(
+            "The default value of input {} has to be a constant",
+            name
+        ));
+    };
+
+    if integer_width(*default) > *bits {
+        resolver.error(format!(
+            "The default value of input {} doesn't fit into {} bits",
+            name, bits
+        ));
+    }
+}
@@ -1,6 +1,8 @@
 pub mod argument;
+pub mod bundle;
 pub mod decorator;
 pub mod expression;
 pub mod program;
+pub mod source_info;
 pub mod variable_definition;
 pub mod module;
\ No newline at end of file
@@ -0,0 +1,15 @@
+use super::decorator::PortType;
+
+#[derive(Debug, Clone)]
+pub struct BundleField {
+    pub name: String,
+    pub port_type: PortType,
+}
+
+// a named group of wires, like the address, data and write enable lines of a
+// memory bus
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub name: String,
+    pub fields: Vec<BundleField>,
+}
@@ -1,9 +1,41 @@
+use std::sync::Arc;
+
+use crate::parser::datatype::KnownBitWidth;
+
 pub type BitWidth = u32;
 
+// the type of an input or output, either a number of bits, a bundle or an
+// array of either. the width of a bundle is filled in by the resolver.
+#[derive(Debug, Clone)]
+pub enum PortType {
+    Bits(BitWidth),
+    Bundle(String, KnownBitWidth),
+    // `[8; 16]`, sixteen 8 bit wires
+    Array(Box<PortType>, u32),
+}
+
+impl PortType {
+    pub fn width(&self) -> KnownBitWidth {
+        match self {
+            PortType::Bits(bits) => KnownBitWidth::Fixed(*bits),
+            PortType::Bundle(_, width) => width.clone(),
+            PortType::Array(element, length) => {
+                let element = Arc::new(element.width());
+
+                KnownBitWidth::Array(vec![element; *length as usize])
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Decorator {
-    Out(Option<BitWidth>, Option<String>),
-    In(BitWidth, Option<String>),
-    Wire(BitWidth),
+    Out(Option<PortType>, Option<String>),
+    In(PortType, Option<String>),
+    InOut(BitWidth, Option<String>),
+    // `None` if the width is inferred from what the wire is assigned
+    Wire(Option<PortType>),
     Clock(Option<BitWidth>),
+    // a top-level variable that a module reads
+    Global,
 }
@@ -1,9 +1,6 @@
 use std::{collections::HashMap, sync::Arc};
 
-use crate::parser::{
-    datatype::{GetBitWidth, KnownBitWidth},
-    ParserState,
-};
+use crate::parser::datatype::KnownBitWidth;
 
 use super::argument::Argument;
 
@@ -14,10 +11,10 @@ pub struct ExpressionWithWidth {
 }
 
 impl ExpressionWithWidth {
-    pub fn new(expression: Expression, state: &ParserState) -> Self {
+    pub fn new(expression: Expression) -> Self {
         Self {
-            width: expression.get_bit_width(state),
             expression,
+            width: KnownBitWidth::Unresolved,
         }
     }
 }
@@ -31,6 +28,8 @@ pub enum Expression {
     Extract(Extract),
     Combine(Combine),
     ModuleUse(ModuleUse),
+    Drive(Drive),
+    Repeat(Repeat),
     String(String),
 }
 
@@ -62,6 +61,8 @@ pub enum ExtractInner {
     Bit(u32),
     Range(u32, u32),
     Name(String),
+    // an element of an array
+    Element(u32),
 }
 
 #[derive(Debug, Clone)]
@@ -70,8 +71,34 @@ pub enum Combine {
     Obj(HashMap<String, Expression>),
 }
 
+// a tri-state driver, the output is high-Z unless `enable` is 1
+#[derive(Debug, Clone)]
+pub struct Drive {
+    pub value: Arc<ExpressionWithWidth>,
+    pub enable: Arc<ExpressionWithWidth>,
+}
+
+// `[value; count]`, an array with `count` copies of `value`. every copy is
+// lowered on its own, identical logic ends up shared by the netlist builder,
+// but repeating the use of an external module creates that many instances.
+#[derive(Debug, Clone)]
+pub struct Repeat {
+    pub value: Arc<ExpressionWithWidth>,
+    pub count: u32,
+}
+
+// the resolver names every positional argument after the input it connects
+// to, so that arguments can be looked up by name afterwards
 #[derive(Debug, Clone)]
 pub struct ModuleUse {
     pub name: String,
-    pub arguments: HashMap<String, Argument>,
+    pub arguments: Vec<Argument>,
+}
+
+impl ModuleUse {
+    pub fn argument(&self, input: &str) -> Option<&Argument> {
+        self.arguments
+            .iter()
+            .find(|argument| argument.name.as_deref() == Some(input))
+    }
 }
@@ -3,7 +3,7 @@ use crate::{
     parser::{datatype::KnownBitWidth, ParserModuleVariableData},
 };
 
-use super::program::ProgramStatement;
+use super::{program::ProgramStatement, source_info::SourceInfo};
 
 #[derive(Debug, Clone)]
 pub struct Module {
@@ -12,15 +12,19 @@ pub struct Module {
 
     pub inputs: Vec<ParserModuleVariableData>,
     pub outputs: Vec<ParserModuleVariableData>,
+    pub inouts: Vec<ParserModuleVariableData>,
+    // the line with the name of the module
+    pub source: SourceInfo,
 }
 
 #[derive(Debug, Clone)]
 pub struct ExternalModuleVariableData {
     pub name: String,
-    pub external_name: String,
 
     pub width: KnownBitWidth,
     pub position: Coordinate,
+    // the value an input is tied to when it isn't connected
+    pub default: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -32,4 +36,6 @@ pub struct ExternalModule {
 
     pub inputs: Vec<ExternalModuleVariableData>,
     pub outputs: Vec<ExternalModuleVariableData>,
+    // pins that are both read and driven, like the data pins of a RAM
+    pub inouts: Vec<ExternalModuleVariableData>,
 }
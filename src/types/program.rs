@@ -1,15 +1,17 @@
-
 use super::{
+    bundle::Bundle,
     expression::Expression,
     module::{ExternalModule, Module},
-    variable_definition::VariableDefinitions,
+    variable_definition::{Destructuring, VariableDefinitions},
 };
 
 #[derive(Debug, Clone)]
 pub enum ProgramStatement {
     VariableDefinitions(VariableDefinitions),
+    Destructuring(Destructuring),
     Module(Module),
     ExternalModule(ExternalModule),
+    Bundle(Bundle),
     Expression(Expression),
 }
 
@@ -0,0 +1,15 @@
+use crate::preprocessor::SourceLocation;
+
+// where a statement starts, and the lints that an `// allow(width)` at the
+// end of its first line turns off for it
+#[derive(Debug, Clone, Default)]
+pub struct SourceInfo {
+    pub location: Option<SourceLocation>,
+    pub allowed: Vec<String>,
+}
+
+impl SourceInfo {
+    pub fn allows(&self, lint: &str) -> bool {
+        self.allowed.iter().any(|allowed| allowed == lint)
+    }
+}
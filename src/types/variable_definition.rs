@@ -1,8 +1,14 @@
-use super::{decorator::Decorator, expression::ExpressionWithWidth};
+use super::{
+    decorator::{Decorator, PortType},
+    expression::ExpressionWithWidth,
+    source_info::SourceInfo,
+};
 
 #[derive(Debug, Clone)]
 pub struct VariableDefinition {
     pub name: String,
+    // `x: 12 = ...`, the value is checked against it and converted
+    pub port_type: Option<PortType>,
     pub value: Option<ExpressionWithWidth>,
 }
 
@@ -10,4 +16,24 @@ pub struct VariableDefinition {
 pub struct VariableDefinitions {
     pub definitions: Vec<VariableDefinition>,
     pub decorator: Option<Decorator>,
+    pub source: SourceInfo,
+}
+
+// a name bound by destructuring, `output` is `None` for a positional field
+// until the resolver looks up the output at that position
+#[derive(Debug, Clone)]
+pub struct DestructuredField {
+    pub output: Option<String>,
+    pub name: String,
+}
+
+// `(sum, carry) = half_adder(...)` binds the outputs of a module in the order
+// they are declared, `{sum: s, carry: c} = ...` binds them by name. the
+// resolver turns it into plain definitions.
+#[derive(Debug, Clone)]
+pub struct Destructuring {
+    pub fields: Vec<DestructuredField>,
+    pub value: ExpressionWithWidth,
+    pub decorator: Option<Decorator>,
+    pub source: SourceInfo,
 }
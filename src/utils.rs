@@ -1 +1,2 @@
+pub mod indexed_name;
 pub mod integer_width;
@@ -0,0 +1,11 @@
+// the name of element `index` of `name`, like the wires created by `out[i]`
+pub fn indexed_name(name: &str, index: u32) -> String {
+    format!("{}[{}]", name, index)
+}
+
+// splits `out[3]` into `out` and 3, the last index is split off first
+pub fn split_indexed_name(name: &str) -> Option<(&str, u32)> {
+    let (base, index) = name.strip_suffix(']')?.rsplit_once('[')?;
+
+    Some((base, index.parse().ok()?))
+}
@@ -5,3 +5,12 @@ pub fn integer_width(int: u32) -> u32 {
         int.ilog2() + 1
     }
 }
+
+// the number of bits needed to address `int` different values
+pub fn ceil_log2(int: u32) -> u32 {
+    if int <= 1 {
+        0
+    } else {
+        (int - 1).ilog2() + 1
+    }
+}